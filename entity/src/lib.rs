@@ -3,5 +3,12 @@
 pub mod prelude;
 
 pub mod block_checkpoint;
+pub mod chain_state;
+pub mod contract_config;
 pub mod order;
+pub mod order_event;
+pub mod order_settlement;
+pub mod order_token;
+pub mod pending_fill;
 pub mod sea_orm_active_enums;
+pub mod spending_counter;
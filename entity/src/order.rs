@@ -27,9 +27,35 @@ pub struct Model {
     #[sea_orm(column_type = "VarBinary(StringLen::None)", nullable)]
     pub call_data: Option<Vec<u8>>,
     pub order_status: OrderStatus,
+    pub rejection_reason: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
-pub enum Relation {}
+pub enum Relation {
+    #[sea_orm(has_one = "super::order_settlement::Entity")]
+    OrderSettlement,
+    #[sea_orm(has_many = "super::order_token::Entity")]
+    OrderToken,
+    #[sea_orm(has_one = "super::pending_fill::Entity")]
+    PendingFill,
+}
+
+impl Related<super::order_settlement::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OrderSettlement.def()
+    }
+}
+
+impl Related<super::order_token::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OrderToken.def()
+    }
+}
+
+impl Related<super::pending_fill::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PendingFill.def()
+    }
+}
 
 impl ActiveModelBehavior for ActiveModel {}
@@ -1,4 +1,11 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.3
 
 pub use super::block_checkpoint::Entity as BlockCheckpoint;
+pub use super::chain_state::Entity as ChainState;
+pub use super::contract_config::Entity as ContractConfig;
 pub use super::order::Entity as Order;
+pub use super::order_event::Entity as OrderEvent;
+pub use super::order_settlement::Entity as OrderSettlement;
+pub use super::order_token::Entity as OrderToken;
+pub use super::pending_fill::Entity as PendingFill;
+pub use super::spending_counter::Entity as SpendingCounter;
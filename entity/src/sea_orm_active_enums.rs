@@ -2,6 +2,17 @@
 
 use sea_orm::entity::prelude::*;
 
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "order_event_type")]
+pub enum OrderEventType {
+    #[sea_orm(string_value = "OrderCreated")]
+    OrderCreated,
+    #[sea_orm(string_value = "OrderFilled")]
+    OrderFilled,
+    #[sea_orm(string_value = "OrderWithdrawn")]
+    OrderWithdrawn,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
 #[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "order_status")]
 pub enum OrderStatus {
@@ -12,3 +23,12 @@ pub enum OrderStatus {
     #[sea_orm(string_value = "Withdrawn")]
     Withdrawn,
 }
+
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "token_side")]
+pub enum TokenSide {
+    #[sea_orm(string_value = "Input")]
+    Input,
+    #[sea_orm(string_value = "Output")]
+    Output,
+}
@@ -3,6 +3,16 @@ pub use sea_orm_migration::prelude::*;
 mod m20250101_000001_create_order;
 mod m20250101_000002_create_block_checkpoint;
 mod m20250101_000003_create_order_status_enum_and_column;
+mod m20250101_000004_create_order_token;
+mod m20250101_000005_create_spending_counter;
+mod m20250101_000006_add_order_rejection_reason;
+mod m20250101_000007_create_contract_config;
+mod m20250101_000008_create_order_settlement;
+mod m20250101_000009_create_order_event;
+mod m20250101_000010_create_chain_state;
+mod m20250101_000011_unique_block_checkpoint_per_chain;
+mod m20250101_000012_add_chain_controls;
+mod m20250101_000013_create_pending_fill;
 
 pub struct Migrator;
 
@@ -13,6 +23,16 @@ impl MigratorTrait for Migrator {
             Box::new(m20250101_000001_create_order::Migration),
             Box::new(m20250101_000002_create_block_checkpoint::Migration),
             Box::new(m20250101_000003_create_order_status_enum_and_column::Migration),
+            Box::new(m20250101_000004_create_order_token::Migration),
+            Box::new(m20250101_000005_create_spending_counter::Migration),
+            Box::new(m20250101_000006_add_order_rejection_reason::Migration),
+            Box::new(m20250101_000007_create_contract_config::Migration),
+            Box::new(m20250101_000008_create_order_settlement::Migration),
+            Box::new(m20250101_000009_create_order_event::Migration),
+            Box::new(m20250101_000010_create_chain_state::Migration),
+            Box::new(m20250101_000011_unique_block_checkpoint_per_chain::Migration),
+            Box::new(m20250101_000012_add_chain_controls::Migration),
+            Box::new(m20250101_000013_create_pending_fill::Migration),
         ]
     }
 }
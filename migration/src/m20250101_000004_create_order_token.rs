@@ -0,0 +1,113 @@
+use extension::postgres::Type;
+use sea_orm::DbBackend;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        match db.get_database_backend() {
+            DbBackend::MySql | DbBackend::Sqlite => {}
+            DbBackend::Postgres => {
+                manager
+                    .create_type(
+                        Type::create()
+                            .as_enum(TokenSide::Enum)
+                            .values([TokenSide::Input, TokenSide::Output])
+                            .to_owned(),
+                    )
+                    .await?;
+            }
+        }
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(OrderToken::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OrderToken::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(OrderToken::OrderId).binary().not_null())
+                    .col(
+                        ColumnDef::new(OrderToken::Side)
+                            .custom(TokenSide::Enum)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(OrderToken::Position).integer().not_null())
+                    .col(ColumnDef::new(OrderToken::TokenAddress).binary().not_null())
+                    .col(ColumnDef::new(OrderToken::TokenId).binary().not_null())
+                    .col(ColumnDef::new(OrderToken::Amount).binary().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(OrderToken::Table, OrderToken::OrderId)
+                            .to(
+                                crate::m20250101_000001_create_order::Order::Table,
+                                crate::m20250101_000001_create_order::Order::OrderId,
+                            )
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_order_token_order_id")
+                    .table(OrderToken::Table)
+                    .col(OrderToken::OrderId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OrderToken::Table).to_owned())
+            .await?;
+
+        let db = manager.get_connection();
+
+        match db.get_database_backend() {
+            DbBackend::MySql | DbBackend::Sqlite => {}
+            DbBackend::Postgres => {
+                manager
+                    .drop_type(Type::drop().name(TokenSide::Enum).to_owned())
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum OrderToken {
+    Table,
+    Id,
+    OrderId,
+    Side,
+    Position,
+    TokenAddress,
+    TokenId,
+    Amount,
+}
+
+#[derive(DeriveIden)]
+enum TokenSide {
+    #[sea_orm(iden = "token_side")]
+    Enum,
+    #[sea_orm(iden = "Input")]
+    Input,
+    #[sea_orm(iden = "Output")]
+    Output,
+}
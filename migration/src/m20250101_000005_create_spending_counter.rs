@@ -0,0 +1,85 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SpendingCounter::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SpendingCounter::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(SpendingCounter::ChainId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(SpendingCounter::Day).date().not_null())
+                    .col(
+                        ColumnDef::new(SpendingCounter::NativeSpent)
+                            .double()
+                            .not_null()
+                            .default(0.0),
+                    )
+                    .col(
+                        ColumnDef::new(SpendingCounter::NotionalFilled)
+                            .double()
+                            .not_null()
+                            .default(0.0),
+                    )
+                    .col(
+                        ColumnDef::new(SpendingCounter::FillCount)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(SpendingCounter::UpdatedAt)
+                            .date_time()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_string())
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_spending_counter_chain_id_day")
+                    .table(SpendingCounter::Table)
+                    .col(SpendingCounter::ChainId)
+                    .col(SpendingCounter::Day)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SpendingCounter::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SpendingCounter {
+    Table,
+    Id,
+    ChainId,
+    Day,
+    NativeSpent,
+    NotionalFilled,
+    FillCount,
+    UpdatedAt,
+}
@@ -0,0 +1,72 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ContractConfig::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ContractConfig::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ContractConfig::ChainId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ContractConfig::Key).string().not_null())
+                    .col(ColumnDef::new(ContractConfig::Value).binary().not_null())
+                    .col(
+                        ColumnDef::new(ContractConfig::BlockNumber)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ContractConfig::UpdatedAt)
+                            .date_time()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_string())
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_contract_config_chain_id_key")
+                    .table(ContractConfig::Table)
+                    .col(ContractConfig::ChainId)
+                    .col(ContractConfig::Key)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ContractConfig::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ContractConfig {
+    Table,
+    Id,
+    ChainId,
+    Key,
+    Value,
+    BlockNumber,
+    UpdatedAt,
+}
@@ -0,0 +1,95 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(OrderSettlement::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OrderSettlement::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(OrderSettlement::OrderId)
+                            .binary()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(OrderSettlement::ChainId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(OrderSettlement::TxHash).binary().not_null())
+                    .col(
+                        ColumnDef::new(OrderSettlement::BlockNumber)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(OrderSettlement::Filler).binary().not_null())
+                    .col(
+                        ColumnDef::new(OrderSettlement::PaidToUs)
+                            .boolean()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OrderSettlement::SettledAt)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(OrderSettlement::Dispatcher).binary())
+                    .col(ColumnDef::new(OrderSettlement::MessageBlockNumber).big_unsigned())
+                    .col(ColumnDef::new(OrderSettlement::BlockConfirmations).integer())
+                    .col(ColumnDef::new(OrderSettlement::MessageSender).binary())
+                    .col(ColumnDef::new(OrderSettlement::MessageReceiver).binary())
+                    .col(ColumnDef::new(OrderSettlement::HashedData).binary())
+                    .col(ColumnDef::new(OrderSettlement::MessageData).binary())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(OrderSettlement::Table, OrderSettlement::OrderId)
+                            .to(
+                                crate::m20250101_000001_create_order::Order::Table,
+                                crate::m20250101_000001_create_order::Order::OrderId,
+                            )
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OrderSettlement::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum OrderSettlement {
+    Table,
+    Id,
+    OrderId,
+    ChainId,
+    TxHash,
+    BlockNumber,
+    Filler,
+    PaidToUs,
+    SettledAt,
+    Dispatcher,
+    MessageBlockNumber,
+    BlockConfirmations,
+    MessageSender,
+    MessageReceiver,
+    HashedData,
+    MessageData,
+}
@@ -0,0 +1,158 @@
+use extension::postgres::Type;
+use sea_orm::DbBackend;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        match db.get_database_backend() {
+            DbBackend::MySql | DbBackend::Sqlite => {}
+            DbBackend::Postgres => {
+                manager
+                    .create_type(
+                        Type::create()
+                            .as_enum(OrderEventType::Enum)
+                            .values([
+                                OrderEventType::OrderCreated,
+                                OrderEventType::OrderFilled,
+                                OrderEventType::OrderWithdrawn,
+                            ])
+                            .to_owned(),
+                    )
+                    .await?;
+            }
+        }
+
+        // Orders can be rolled back on reorgs, so events are not tied to them by a foreign key
+        manager
+            .create_table(
+                Table::create()
+                    .table(OrderEvent::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OrderEvent::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(OrderEvent::ChainId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(OrderEvent::OrderId).binary().not_null())
+                    .col(
+                        ColumnDef::new(OrderEvent::EventType)
+                            .custom(OrderEventType::Enum)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OrderEvent::BlockNumber)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(OrderEvent::BlockHash).binary().not_null())
+                    .col(ColumnDef::new(OrderEvent::TxHash).binary().not_null())
+                    .col(
+                        ColumnDef::new(OrderEvent::LogIndex)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(OrderEvent::Payload).json_binary().not_null())
+                    .col(
+                        ColumnDef::new(OrderEvent::CreatedAt)
+                            .date_time()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_string())
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_order_event_chain_id_tx_hash_log_index")
+                    .table(OrderEvent::Table)
+                    .col(OrderEvent::ChainId)
+                    .col(OrderEvent::TxHash)
+                    .col(OrderEvent::LogIndex)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_order_event_order_id")
+                    .table(OrderEvent::Table)
+                    .col(OrderEvent::OrderId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_order_event_chain_id_block_number")
+                    .table(OrderEvent::Table)
+                    .col(OrderEvent::ChainId)
+                    .col(OrderEvent::BlockNumber)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OrderEvent::Table).to_owned())
+            .await?;
+
+        let db = manager.get_connection();
+
+        match db.get_database_backend() {
+            DbBackend::MySql | DbBackend::Sqlite => {}
+            DbBackend::Postgres => {
+                manager
+                    .drop_type(Type::drop().name(OrderEventType::Enum).to_owned())
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum OrderEvent {
+    Table,
+    Id,
+    ChainId,
+    OrderId,
+    EventType,
+    BlockNumber,
+    BlockHash,
+    TxHash,
+    LogIndex,
+    Payload,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum OrderEventType {
+    #[sea_orm(iden = "order_event_type")]
+    Enum,
+    #[sea_orm(iden = "OrderCreated")]
+    OrderCreated,
+    #[sea_orm(iden = "OrderFilled")]
+    OrderFilled,
+    #[sea_orm(iden = "OrderWithdrawn")]
+    OrderWithdrawn,
+}
@@ -0,0 +1,48 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Only the highest checkpoint of each chain is kept
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "DELETE FROM block_checkpoint AS old \
+                USING block_checkpoint AS new \
+                WHERE old.chain_id = new.chain_id \
+                AND (old.height, old.id) < (new.height, new.id)",
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_block_checkpoint_chain_id")
+                    .table(BlockCheckpoint::Table)
+                    .col(BlockCheckpoint::ChainId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_block_checkpoint_chain_id")
+                    .table(BlockCheckpoint::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BlockCheckpoint {
+    Table,
+    ChainId,
+}
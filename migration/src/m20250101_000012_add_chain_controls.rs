@@ -0,0 +1,62 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ChainState::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(ChainState::FillerPaused)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .add_column_if_not_exists(
+                        ColumnDef::new(ChainState::ListenerPaused)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .add_column_if_not_exists(
+                        ColumnDef::new(ChainState::ReindexFromBlock)
+                            .big_unsigned()
+                            .null(),
+                    )
+                    .add_column_if_not_exists(
+                        ColumnDef::new(ChainState::ReindexToBlock)
+                            .big_unsigned()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ChainState::Table)
+                    .drop_column(ChainState::FillerPaused)
+                    .drop_column(ChainState::ListenerPaused)
+                    .drop_column(ChainState::ReindexFromBlock)
+                    .drop_column(ChainState::ReindexToBlock)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ChainState {
+    Table,
+    FillerPaused,
+    ListenerPaused,
+    ReindexFromBlock,
+    ReindexToBlock,
+}
@@ -0,0 +1,90 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PendingFill::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PendingFill::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PendingFill::OrderId)
+                            .binary()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PendingFill::ChainId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PendingFill::DestinationChainId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PendingFill::Day).date().not_null())
+                    .col(ColumnDef::new(PendingFill::TxHash).binary())
+                    .col(ColumnDef::new(PendingFill::ReservedFee).double().not_null())
+                    .col(
+                        ColumnDef::new(PendingFill::ReservedNotional)
+                            .double()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PendingFill::MessagingFee)
+                            .double()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PendingFill::CreatedAt)
+                            .date_time()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_string())
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PendingFill::Table, PendingFill::OrderId)
+                            .to(
+                                crate::m20250101_000001_create_order::Order::Table,
+                                crate::m20250101_000001_create_order::Order::OrderId,
+                            )
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PendingFill::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PendingFill {
+    Table,
+    Id,
+    OrderId,
+    ChainId,
+    DestinationChainId,
+    Day,
+    TxHash,
+    ReservedFee,
+    ReservedNotional,
+    MessagingFee,
+    CreatedAt,
+}
@@ -0,0 +1,493 @@
+mod notifier;
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use alloy::transports::http::reqwest::Client;
+use eyre::Result;
+use notifier::{notifier, Notifier};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tracing::{debug, error, info, warn};
+
+use crate::{context::AlertsConfig, metrics::metrics};
+
+const DEFAULT_DEDUP_WINDOW: u64 = 300;
+const DEFAULT_MAX_PER_MINUTE: usize = 20;
+const DEFAULT_TEMPLATE: &str = "[{severity}] {chain} {kind}: {message}";
+const NOTIFICATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+static ALERTS: OnceLock<mpsc::UnboundedSender<Alert>> = OnceLock::new();
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+/// An operational event that someone should look at.
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub severity: Severity,
+    pub kind: &'static str,
+    pub chain: String,
+    pub message: String,
+    /// Tells apart the alerts of a kind on a chain that are not repeats of each other, e.g.
+    /// the order id of a reverted fill.
+    pub dedup_key: Option<String>,
+}
+
+impl Alert {
+    pub fn new(
+        severity: Severity,
+        kind: &'static str,
+        chain: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            severity,
+            kind,
+            chain: chain.into(),
+            message: message.into(),
+            dedup_key: None,
+        }
+    }
+
+    pub fn with_dedup_key(mut self, dedup_key: impl Into<String>) -> Self {
+        self.dedup_key = Some(dedup_key.into());
+        self
+    }
+
+    fn render(&self, template: &str) -> String {
+        template
+            .replace("{severity}", self.severity.as_str())
+            .replace("{kind}", self.kind)
+            .replace("{chain}", &self.chain)
+            .replace("{message}", &self.message)
+    }
+}
+
+/// Logs the alert, and notifies the configured sinks once `init` has been called.
+pub fn raise(alert: Alert) {
+    match alert.severity {
+        Severity::Info => info!(
+            target: "alert",
+            kind = alert.kind,
+            chain = alert.chain,
+            "{}",
+            alert.message
+        ),
+        Severity::Warning => warn!(
+            target: "alert",
+            kind = alert.kind,
+            chain = alert.chain,
+            "{}",
+            alert.message
+        ),
+        Severity::Critical => error!(
+            target: "alert",
+            kind = alert.kind,
+            chain = alert.chain,
+            "{}",
+            alert.message
+        ),
+    }
+    if let Some(alerts) = ALERTS.get() {
+        let _ = alerts.send(alert);
+    }
+}
+
+/// Starts sending the raised alerts to the configured sinks.
+pub fn init(config: &AlertsConfig) -> Result<()> {
+    if config.sinks.is_empty() {
+        return Ok(());
+    }
+    let (sender, receiver) = mpsc::unbounded_channel();
+    ALERTS
+        .set(sender)
+        .map_err(|_| eyre::eyre!("Alerts are already initialized"))?;
+    let dispatcher = Dispatcher::new(config)?;
+    tokio::spawn(dispatcher.run(receiver));
+    Ok(())
+}
+
+struct Sink {
+    notifier: Box<dyn Notifier>,
+    min_severity: Severity,
+    template: String,
+    max_per_minute: usize,
+    sent: VecDeque<Instant>,
+}
+
+impl Sink {
+    /// Whether the sink is under its rate limit, counting the notification if it is.
+    fn try_acquire(&mut self, now: Instant) -> bool {
+        while self
+            .sent
+            .front()
+            .is_some_and(|sent| now.duration_since(*sent) >= Duration::from_secs(60))
+        {
+            self.sent.pop_front();
+        }
+        if self.sent.len() >= self.max_per_minute {
+            return false;
+        }
+        self.sent.push_back(now);
+        true
+    }
+}
+
+/// Alert of a kind and chain last sent, with the number of repeats not sent since.
+struct Sent {
+    at: Instant,
+    suppressed: u32,
+}
+
+struct Dispatcher {
+    client: Client,
+    sinks: Vec<Sink>,
+    dedup_window: Duration,
+    sent: HashMap<(&'static str, String, Option<String>), Sent>,
+}
+
+impl Dispatcher {
+    fn new(config: &AlertsConfig) -> Result<Self> {
+        let sinks = config
+            .sinks
+            .iter()
+            .map(|sink| Sink {
+                notifier: notifier(&sink.notifier),
+                min_severity: sink.min_severity.unwrap_or(Severity::Warning),
+                template: sink
+                    .template
+                    .clone()
+                    .unwrap_or(DEFAULT_TEMPLATE.to_string()),
+                max_per_minute: config.max_per_minute.unwrap_or(DEFAULT_MAX_PER_MINUTE),
+                sent: VecDeque::new(),
+            })
+            .collect();
+        Ok(Self {
+            client: Client::builder().timeout(NOTIFICATION_TIMEOUT).build()?,
+            sinks,
+            dedup_window: Duration::from_secs(config.dedup_window.unwrap_or(DEFAULT_DEDUP_WINDOW)),
+            sent: HashMap::new(),
+        })
+    }
+
+    /// The alert to send, or `None` when the same kind of alert with the same dedup key was
+    /// sent on the chain within the dedup window. Repeats are counted into the next alert sent.
+    fn deduplicate(&mut self, mut alert: Alert, now: Instant) -> Option<Alert> {
+        let key = (alert.kind, alert.chain.clone(), alert.dedup_key.clone());
+        if let Some(sent) = self.sent.get_mut(&key) {
+            if now.duration_since(sent.at) < self.dedup_window {
+                sent.suppressed += 1;
+                return None;
+            }
+            if sent.suppressed > 0 {
+                alert.message = format!(
+                    "{} ({} similar alerts suppressed)",
+                    alert.message, sent.suppressed
+                );
+            }
+        }
+        self.sent.insert(
+            key,
+            Sent {
+                at: now,
+                suppressed: 0,
+            },
+        );
+        Some(alert)
+    }
+
+    async fn run(mut self, mut alerts: mpsc::UnboundedReceiver<Alert>) {
+        while let Some(alert) = alerts.recv().await {
+            let now = Instant::now();
+            let kind = alert.kind;
+            let Some(alert) = self.deduplicate(alert, now) else {
+                debug!(kind, "Duplicate alert not sent");
+                continue;
+            };
+            for sink in &mut self.sinks {
+                if alert.severity < sink.min_severity {
+                    continue;
+                }
+                let name = sink.notifier.name();
+                if !sink.try_acquire(now) {
+                    warn!(sink = name, kind = alert.kind, "Alert rate limited");
+                    metrics()
+                        .alert_notifications
+                        .with_label_values(&[name, "rate_limited"])
+                        .inc();
+                    continue;
+                }
+                let text = alert.render(&sink.template);
+                let result = sink
+                    .notifier
+                    .request(&self.client, &alert, &text)
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status());
+                // Failures are only logged, raising them would loop
+                let status = match result {
+                    Ok(_) => "sent",
+                    // The URL may hold a token
+                    Err(e) => {
+                        warn!(
+                            sink = name,
+                            kind = alert.kind,
+                            error = %e.without_url(),
+                            "Unable to send the alert"
+                        );
+                        "failed"
+                    }
+                };
+                metrics()
+                    .alert_notifications
+                    .with_label_values(&[name, status])
+                    .inc();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    };
+
+    use axum::{extract::State, http::Uri, Router};
+    use tokio::sync::mpsc;
+
+    use super::{Alert, Dispatcher, Severity};
+    use crate::context::{AlertSinkConfig, AlertsConfig, NotifierConfig};
+
+    type Requests = Arc<Mutex<Vec<(String, serde_json::Value)>>>;
+
+    /// Answers every request with `200 OK`, recording its path and JSON body.
+    async fn stub_server() -> (String, Requests) {
+        let requests = Requests::default();
+        let app = Router::new()
+            .fallback(
+                |State(requests): State<Requests>, uri: Uri, body: String| async move {
+                    let body = serde_json::from_str(&body).unwrap();
+                    requests
+                        .lock()
+                        .unwrap()
+                        .push((uri.path().to_string(), body));
+                },
+            )
+            .with_state(requests.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url, requests)
+    }
+
+    fn sink(notifier: NotifierConfig) -> AlertSinkConfig {
+        AlertSinkConfig {
+            notifier,
+            min_severity: None,
+            template: None,
+        }
+    }
+
+    fn alert(kind: &'static str, chain: &str) -> Alert {
+        Alert::new(Severity::Warning, kind, chain, "Listener is behind")
+    }
+
+    fn dispatcher(max_per_minute: usize) -> Dispatcher {
+        Dispatcher::new(&AlertsConfig {
+            dedup_window: Some(60),
+            max_per_minute: Some(max_per_minute),
+            sinks: vec![AlertSinkConfig {
+                notifier: NotifierConfig::Slack {
+                    url: "http://localhost/slack".into(),
+                },
+                min_severity: None,
+                template: None,
+            }],
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_sinks_config() {
+        let config: AlertsConfig = config::Config::builder()
+            .add_source(config::File::from_str(
+                r#"
+                    [[sinks]]
+                    type = "slack"
+                    url = "https://hooks.slack.com/services/x"
+                    min_severity = "critical"
+
+                    [[sinks]]
+                    type = "telegram"
+                    bot_token = "123:abc"
+                    chat_id = "-42"
+                "#,
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        assert_eq!(config.sinks[0].min_severity, Some(Severity::Critical));
+        assert!(matches!(
+            config.sinks[1].notifier,
+            NotifierConfig::Telegram { ref chat_id, .. } if chat_id == "-42"
+        ));
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            alert("listener_lagging", "optimism").render(super::DEFAULT_TEMPLATE),
+            "[warning] optimism listener_lagging: Listener is behind"
+        );
+    }
+
+    #[test]
+    fn test_deduplicate() {
+        let mut dispatcher = dispatcher(10);
+        let now = Instant::now();
+        assert!(dispatcher
+            .deduplicate(alert("listener_lagging", "a"), now)
+            .is_some());
+        assert!(dispatcher
+            .deduplicate(alert("listener_lagging", "a"), now)
+            .is_none());
+        assert!(dispatcher
+            .deduplicate(
+                alert("listener_lagging", "a"),
+                now + Duration::from_secs(30)
+            )
+            .is_none());
+        // Other chains and kinds are not duplicates
+        assert!(dispatcher
+            .deduplicate(alert("listener_lagging", "b"), now)
+            .is_some());
+        assert!(dispatcher
+            .deduplicate(alert("fill_reverted", "a"), now)
+            .is_some());
+        assert!(dispatcher
+            .deduplicate(alert("fill_reverted", "a").with_dedup_key("0x01"), now)
+            .is_some());
+        assert!(dispatcher
+            .deduplicate(alert("fill_reverted", "a").with_dedup_key("0x01"), now)
+            .is_none());
+
+        let alert = dispatcher
+            .deduplicate(
+                alert("listener_lagging", "a"),
+                now + Duration::from_secs(60),
+            )
+            .unwrap();
+        assert_eq!(
+            alert.message,
+            "Listener is behind (2 similar alerts suppressed)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_delivers_alerts() {
+        let (url, requests) = stub_server().await;
+        let dispatcher = Dispatcher::new(&AlertsConfig {
+            dedup_window: Some(60),
+            max_per_minute: None,
+            sinks: vec![
+                sink(NotifierConfig::Webhook {
+                    url: format!("{url}/webhook").as_str().into(),
+                }),
+                sink(NotifierConfig::Slack {
+                    url: format!("{url}/slack").as_str().into(),
+                }),
+                sink(NotifierConfig::Telegram {
+                    bot_token: "123:abc".into(),
+                    chat_id: "-42".to_string(),
+                    api_url: Some(url),
+                }),
+            ],
+        })
+        .unwrap();
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let reverted = |order_id: &str| {
+            Alert::new(
+                Severity::Critical,
+                "fill_reverted",
+                "optimism",
+                format!("Fill of order {order_id} reverted"),
+            )
+            .with_dedup_key(order_id)
+        };
+        sender.send(reverted("0x01")).unwrap();
+        sender.send(reverted("0x01")).unwrap();
+        sender.send(reverted("0x02")).unwrap();
+        // Below the default minimum severity of the sinks
+        sender
+            .send(Alert::new(Severity::Info, "info", "optimism", "Info"))
+            .unwrap();
+        drop(sender);
+        dispatcher.run(receiver).await;
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 6);
+        assert_eq!(requests[0].0, "/webhook");
+        assert_eq!(
+            requests[0].1,
+            serde_json::json!({
+                "severity": "critical",
+                "kind": "fill_reverted",
+                "chain": "optimism",
+                "message": "Fill of order 0x01 reverted",
+                "dedup_key": "0x01",
+                "text": "[critical] optimism fill_reverted: Fill of order 0x01 reverted",
+            })
+        );
+        assert_eq!(requests[1].0, "/slack");
+        assert_eq!(
+            requests[1].1,
+            serde_json::json!({
+                "text": "[critical] optimism fill_reverted: Fill of order 0x01 reverted",
+            })
+        );
+        assert_eq!(requests[2].0, "/bot123:abc/sendMessage");
+        assert_eq!(
+            requests[2].1,
+            serde_json::json!({
+                "chat_id": "-42",
+                "text": "[critical] optimism fill_reverted: Fill of order 0x01 reverted",
+            })
+        );
+        assert_eq!(requests[3].1["dedup_key"], "0x02");
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let mut dispatcher = dispatcher(2);
+        let sink = &mut dispatcher.sinks[0];
+        let now = Instant::now();
+        assert!(sink.try_acquire(now));
+        assert!(sink.try_acquire(now + Duration::from_secs(1)));
+        assert!(!sink.try_acquire(now + Duration::from_secs(2)));
+        assert!(sink.try_acquire(now + Duration::from_secs(60)));
+    }
+}
@@ -0,0 +1,144 @@
+use alloy::transports::http::reqwest::{Client, RequestBuilder};
+use serde_json::json;
+
+use super::Alert;
+use crate::context::{NotifierConfig, Secret};
+
+const DEFAULT_TELEGRAM_API_URL: &str = "https://api.telegram.org";
+
+/// A destination of the alert notifications.
+pub trait Notifier: Send + Sync {
+    /// Label of the sink in the logs and metrics.
+    fn name(&self) -> &'static str;
+
+    /// Request delivering the alert, `text` being the alert rendered with the sink template.
+    fn request(&self, client: &Client, alert: &Alert, text: &str) -> RequestBuilder;
+}
+
+/// Posts the alert fields as JSON.
+pub struct Webhook {
+    url: Secret,
+}
+
+impl Notifier for Webhook {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn request(&self, client: &Client, alert: &Alert, text: &str) -> RequestBuilder {
+        client.post(self.url.expose()).json(&json!({
+            "severity": alert.severity.as_str(),
+            "kind": alert.kind,
+            "chain": alert.chain,
+            "message": alert.message,
+            "dedup_key": alert.dedup_key,
+            "text": text,
+        }))
+    }
+}
+
+/// Slack incoming webhook.
+pub struct Slack {
+    url: Secret,
+}
+
+impl Notifier for Slack {
+    fn name(&self) -> &'static str {
+        "slack"
+    }
+
+    fn request(&self, client: &Client, _alert: &Alert, text: &str) -> RequestBuilder {
+        client
+            .post(self.url.expose())
+            .json(&json!({ "text": text }))
+    }
+}
+
+/// Message sent by a Telegram bot to a chat.
+pub struct Telegram {
+    bot_token: Secret,
+    chat_id: String,
+    api_url: String,
+}
+
+impl Notifier for Telegram {
+    fn name(&self) -> &'static str {
+        "telegram"
+    }
+
+    fn request(&self, client: &Client, _alert: &Alert, text: &str) -> RequestBuilder {
+        client
+            .post(format!(
+                "{}/bot{}/sendMessage",
+                self.api_url.trim_end_matches('/'),
+                self.bot_token.expose()
+            ))
+            .json(&json!({ "chat_id": self.chat_id, "text": text }))
+    }
+}
+
+pub fn notifier(config: &NotifierConfig) -> Box<dyn Notifier> {
+    match config {
+        NotifierConfig::Webhook { url } => Box::new(Webhook { url: url.clone() }),
+        NotifierConfig::Slack { url } => Box::new(Slack { url: url.clone() }),
+        NotifierConfig::Telegram {
+            bot_token,
+            chat_id,
+            api_url,
+        } => Box::new(Telegram {
+            bot_token: bot_token.clone(),
+            chat_id: chat_id.clone(),
+            api_url: api_url
+                .clone()
+                .unwrap_or(DEFAULT_TELEGRAM_API_URL.to_string()),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::transports::http::reqwest::Client;
+
+    use super::notifier;
+    use crate::{
+        alert::{Alert, Severity},
+        context::NotifierConfig,
+    };
+
+    fn body(config: NotifierConfig) -> (String, serde_json::Value) {
+        let alert = Alert::new(Severity::Critical, "fill_reverted", "optimism", "Reverted");
+        let request = notifier(&config)
+            .request(&Client::new(), &alert, "text")
+            .build()
+            .unwrap();
+        let body = request.body().unwrap().as_bytes().unwrap();
+        (
+            request.url().to_string(),
+            serde_json::from_slice(body).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_notifier_requests() {
+        let (url, webhook) = body(NotifierConfig::Webhook {
+            url: "http://localhost/alerts".into(),
+        });
+        assert_eq!(url, "http://localhost/alerts");
+        assert_eq!(webhook["severity"], "critical");
+        assert_eq!(webhook["kind"], "fill_reverted");
+        assert_eq!(webhook["chain"], "optimism");
+
+        let (_, slack) = body(NotifierConfig::Slack {
+            url: "http://localhost/slack".into(),
+        });
+        assert_eq!(slack, serde_json::json!({ "text": "text" }));
+
+        let (url, telegram) = body(NotifierConfig::Telegram {
+            bot_token: "123:abc".into(),
+            chat_id: "-42".to_string(),
+            api_url: Some("http://localhost:8081/".to_string()),
+        });
+        assert_eq!(url, "http://localhost:8081/bot123:abc/sendMessage");
+        assert_eq!(telegram["chat_id"], "-42");
+    }
+}
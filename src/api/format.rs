@@ -0,0 +1,229 @@
+use alloy::primitives::{utils::format_units, Address, U256};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use entity::{
+    order, order_event, order_token,
+    sea_orm_active_enums::{OrderStatus, TokenSide},
+};
+use eyre::Result;
+use serde::Serialize;
+
+use crate::{
+    solidity::{
+        address_to_bytes64_vec, bytes64_to_address, chain_selector_to_chain_id, u256_from_le_vec,
+    },
+    token::TokenRegistry,
+};
+
+#[derive(Debug, Serialize)]
+pub struct TokenLegResponse {
+    pub position: i32,
+    pub token: String,
+    pub symbol: Option<String>,
+    pub token_id: String,
+    pub amount: String,
+    /// Amount in whole token units, when the token decimals are configured.
+    pub amount_formatted: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrderEventResponse {
+    pub event_type: String,
+    pub block_number: i64,
+    pub tx_hash: String,
+    pub log_index: i64,
+    pub payload: serde_json::Value,
+    pub indexed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrderResponse {
+    pub id: i32,
+    pub order_id: String,
+    pub chain_id: i64,
+    pub status: String,
+    pub rejection_reason: Option<String>,
+    pub user: String,
+    pub filler: String,
+    pub source_chain_id: Option<u64>,
+    pub destination_chain_id: Option<u64>,
+    pub sponsored: bool,
+    pub primary_filler_deadline: DateTime<Utc>,
+    pub deadline: DateTime<Utc>,
+    pub call_recipient: Option<String>,
+    pub call_data: Option<String>,
+    pub inputs: Vec<TokenLegResponse>,
+    pub outputs: Vec<TokenLegResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub events: Option<Vec<OrderEventResponse>>,
+}
+
+pub fn format_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// Checksummed address for `_bytes64` holding an EVM address, the raw bytes otherwise.
+pub fn format_bytes64(bytes: &[u8]) -> String {
+    match bytes64_to_address(bytes) {
+        Some(address) => address.to_string(),
+        None => format_hex(bytes),
+    }
+}
+
+/// Parses an EVM address or a raw 64 bytes hex value into its persisted `_bytes64` form.
+pub fn parse_bytes64(value: &str) -> Result<Vec<u8>> {
+    let bytes = hex::decode(value.trim_start_matches("0x"))?;
+    match bytes.len() {
+        20 => Ok(address_to_bytes64_vec(Address::from_slice(&bytes))),
+        64 => Ok(bytes),
+        len => Err(eyre::eyre!(
+            "Expected a 20 bytes address or 64 bytes value, got {len} bytes"
+        )),
+    }
+}
+
+pub fn parse_order_status(value: &str) -> Result<OrderStatus> {
+    match value.to_lowercase().as_str() {
+        "created" => Ok(OrderStatus::Created),
+        "filled" => Ok(OrderStatus::Filled),
+        "withdrawn" => Ok(OrderStatus::Withdrawn),
+        _ => Err(eyre::eyre!("Unknown order status {value}")),
+    }
+}
+
+/// Amount in whole token units, without trailing zeros.
+pub fn format_amount(amount: U256, decimals: u8) -> Result<String> {
+    let formatted = format_units(amount, decimals)?;
+    if !formatted.contains('.') {
+        return Ok(formatted);
+    }
+    Ok(formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string())
+}
+
+fn utc(datetime: NaiveDateTime) -> DateTime<Utc> {
+    datetime.and_utc()
+}
+
+/// Token legs are looked up on the chain they live on, inputs on the source chain and
+/// outputs on the destination chain.
+fn token_leg(
+    token: &order_token::Model,
+    chain_id: Option<u64>,
+    token_registry: &TokenRegistry,
+) -> Result<TokenLegResponse> {
+    let amount = u256_from_le_vec(&token.amount)?;
+    let token_config =
+        chain_id.and_then(|chain_id| token_registry.resolve(chain_id, &token.token_address));
+    let amount_formatted = token_config
+        .and_then(|token_config| token_config.decimals)
+        .map(|decimals| format_amount(amount, decimals))
+        .transpose()?;
+
+    Ok(TokenLegResponse {
+        position: token.position,
+        token: format_bytes64(&token.token_address),
+        symbol: token_config.map(|token_config| token_config.symbol.clone()),
+        token_id: u256_from_le_vec(&token.token_id)?.to_string(),
+        amount: amount.to_string(),
+        amount_formatted,
+    })
+}
+
+pub fn order_response(
+    order: &order::Model,
+    tokens: &[order_token::Model],
+    token_registry: &TokenRegistry,
+) -> Result<OrderResponse> {
+    let source_chain_id = chain_selector_to_chain_id(&order.source_chain_selector).ok();
+    let destination_chain_id = chain_selector_to_chain_id(&order.destination_chain_selector).ok();
+
+    let mut inputs = vec![];
+    let mut outputs = vec![];
+    for token in tokens
+        .iter()
+        .filter(|token| token.order_id == order.order_id)
+    {
+        match token.side {
+            TokenSide::Input => inputs.push(token_leg(token, source_chain_id, token_registry)?),
+            TokenSide::Output => {
+                outputs.push(token_leg(token, destination_chain_id, token_registry)?)
+            }
+        }
+    }
+    inputs.sort_by_key(|token| token.position);
+    outputs.sort_by_key(|token| token.position);
+
+    Ok(OrderResponse {
+        id: order.id,
+        order_id: format_hex(&order.order_id),
+        chain_id: order.chain_id,
+        status: format!("{:?}", order.order_status),
+        rejection_reason: order.rejection_reason.clone(),
+        user: format_bytes64(&order.user),
+        filler: format_bytes64(&order.filler),
+        source_chain_id,
+        destination_chain_id,
+        sponsored: order.sponsored,
+        primary_filler_deadline: utc(order.primary_filler_deadline),
+        deadline: utc(order.deadline),
+        call_recipient: order.call_recipient.as_deref().map(format_bytes64),
+        call_data: order.call_data.as_deref().map(format_hex),
+        inputs,
+        outputs,
+        events: None,
+    })
+}
+
+pub fn order_event_response(event: order_event::Model) -> OrderEventResponse {
+    OrderEventResponse {
+        event_type: format!("{:?}", event.event_type),
+        block_number: event.block_number,
+        tx_hash: format_hex(&event.tx_hash),
+        log_index: event.log_index,
+        payload: event.payload,
+        indexed_at: utc(event.created_at),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{address, U256};
+
+    use super::{format_amount, format_bytes64, parse_bytes64, parse_order_status};
+
+    #[test]
+    fn test_bytes64_roundtrip() {
+        let address = "0xa0Ee7A142d267C1f36714E4a8F75612F20a79720";
+        let bytes = parse_bytes64(address).unwrap();
+        assert_eq!(bytes.len(), 64);
+        assert_eq!(format_bytes64(&bytes), address);
+
+        let raw = format!("0x{}", "11".repeat(64));
+        assert_eq!(format_bytes64(&parse_bytes64(&raw).unwrap()), raw);
+
+        assert!(parse_bytes64("0x1234").is_err());
+        assert!(parse_bytes64("not hex").is_err());
+        assert_eq!(
+            parse_bytes64(&address!("a0Ee7A142d267C1f36714E4a8F75612F20a79720").to_string())
+                .unwrap(),
+            bytes
+        );
+    }
+
+    #[test]
+    fn test_format_amount() {
+        assert_eq!(format_amount(U256::from(1_500_000), 6).unwrap(), "1.5");
+        assert_eq!(format_amount(U256::from(1_000_000), 6).unwrap(), "1");
+        assert_eq!(format_amount(U256::from(42), 0).unwrap(), "42");
+        assert_eq!(format_amount(U256::ZERO, 18).unwrap(), "0");
+    }
+
+    #[test]
+    fn test_parse_order_status() {
+        assert!(parse_order_status("Filled").is_ok());
+        assert!(parse_order_status("created").is_ok());
+        assert!(parse_order_status("pending").is_err());
+    }
+}
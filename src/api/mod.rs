@@ -0,0 +1,258 @@
+mod admin;
+pub(crate) mod format;
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use format::{
+    format_hex, order_event_response, order_response, parse_bytes64, parse_order_status,
+    OrderResponse,
+};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::{
+    health::health,
+    repository::{
+        block_checkpoint::BlockCheckpointRepository,
+        chain_state::ChainStateRepository,
+        order::{OrderFilter, OrderRepository},
+        order_event::OrderEventRepository,
+    },
+    runtime::{LiveConfig, LiveConfigReceiver},
+};
+
+const DEFAULT_PAGE_SIZE: u64 = 50;
+const MAX_PAGE_SIZE: u64 = 500;
+
+#[derive(Clone)]
+pub struct ApiState {
+    live_config: LiveConfigReceiver,
+    order_repository: Arc<OrderRepository>,
+    order_event_repository: Arc<OrderEventRepository>,
+    block_checkpoint_repository: Arc<BlockCheckpointRepository>,
+    chain_state_repository: Arc<ChainStateRepository>,
+}
+
+impl ApiState {
+    pub async fn new(postgres_url: String, live_config: LiveConfigReceiver) -> Result<Self> {
+        let order_repository = Arc::new(OrderRepository::new(postgres_url.clone()).await?);
+        let order_event_repository =
+            Arc::new(OrderEventRepository::new(postgres_url.clone()).await?);
+        let block_checkpoint_repository =
+            Arc::new(BlockCheckpointRepository::new(postgres_url.clone()).await?);
+        let chain_state_repository = Arc::new(ChainStateRepository::new(postgres_url).await?);
+        Ok(Self {
+            live_config,
+            order_repository,
+            order_event_repository,
+            block_checkpoint_repository,
+            chain_state_repository,
+        })
+    }
+
+    /// Configuration of the running services, as of the last reload.
+    fn live_config(&self) -> Arc<LiveConfig> {
+        self.live_config.borrow().clone()
+    }
+}
+
+pub enum ApiError {
+    BadRequest(String),
+    NotFound(String),
+    Internal(eyre::Report),
+}
+
+impl From<eyre::Report> for ApiError {
+    fn from(e: eyre::Report) -> Self {
+        ApiError::Internal(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            ApiError::NotFound(message) => (StatusCode::NOT_FOUND, message),
+            ApiError::Internal(e) => {
+                error!(error = %e, "API request failed");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Internal error".to_string(),
+                )
+            }
+        };
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    }
+}
+
+fn bad_request(e: eyre::Report) -> ApiError {
+    ApiError::BadRequest(e.to_string())
+}
+
+fn parse_order_id(order_id: &str) -> Result<Vec<u8>, ApiError> {
+    hex::decode(order_id.trim_start_matches("0x"))
+        .map_err(|_| ApiError::BadRequest("Invalid order id".to_string()))
+}
+
+/// Read-only routes, along with the admin routes when `admin_token` is set.
+pub fn router(state: ApiState, admin_token: Option<String>) -> Router {
+    let router = Router::new()
+        .route("/orders", get(list_orders))
+        .route("/orders/{order_id}", get(get_order))
+        .route("/chains", get(list_chains))
+        .with_state(state.clone());
+    match admin_token {
+        Some(admin_token) => router.nest("/admin", admin::router(state, admin_token)),
+        None => router,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ListOrdersQuery {
+    chain_id: Option<u64>,
+    status: Option<String>,
+    user: Option<String>,
+    filler: Option<String>,
+    /// Token address, matched against both inputs and outputs.
+    token: Option<String>,
+    deadline_after: Option<DateTime<Utc>>,
+    deadline_before: Option<DateTime<Utc>>,
+    cursor: Option<String>,
+    limit: Option<u64>,
+}
+
+impl ListOrdersQuery {
+    fn filter(&self) -> Result<OrderFilter> {
+        Ok(OrderFilter {
+            chain_id: self.chain_id,
+            status: self.status.as_deref().map(parse_order_status).transpose()?,
+            user: self.user.as_deref().map(parse_bytes64).transpose()?,
+            filler: self.filler.as_deref().map(parse_bytes64).transpose()?,
+            deadline_after: self.deadline_after.map(|deadline| deadline.naive_utc()),
+            deadline_before: self.deadline_before.map(|deadline| deadline.naive_utc()),
+            token: self.token.as_deref().map(parse_bytes64).transpose()?,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct OrdersPage {
+    orders: Vec<OrderResponse>,
+    /// Passed as `cursor` to get the next page, `None` on the last page.
+    next_cursor: Option<String>,
+}
+
+async fn list_orders(
+    State(state): State<ApiState>,
+    Query(query): Query<ListOrdersQuery>,
+) -> Result<Json<OrdersPage>, ApiError> {
+    let filter = query.filter().map_err(bad_request)?;
+    let cursor = query
+        .cursor
+        .as_deref()
+        .map(str::parse::<i32>)
+        .transpose()
+        .map_err(|_| ApiError::BadRequest("Invalid cursor".to_string()))?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+
+    let orders = state
+        .order_repository
+        .list_orders(&filter, cursor, limit)
+        .await?;
+    let tokens = state
+        .order_repository
+        .get_tokens_of_orders(orders.iter().map(|order| order.order_id.clone()).collect())
+        .await?;
+    let live_config = state.live_config();
+    let next_cursor = match orders.last() {
+        Some(order) if orders.len() as u64 == limit => Some(order.id.to_string()),
+        _ => None,
+    };
+    let orders = orders
+        .iter()
+        .map(|order| order_response(order, &tokens, &live_config.token_registry))
+        .collect::<Result<_>>()?;
+
+    Ok(Json(OrdersPage {
+        orders,
+        next_cursor,
+    }))
+}
+
+async fn get_order(
+    State(state): State<ApiState>,
+    Path(order_id): Path<String>,
+) -> Result<Json<OrderResponse>, ApiError> {
+    let order_id = parse_order_id(&order_id)?;
+    let order = state
+        .order_repository
+        .find_order(order_id.clone())
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Order {} not found", format_hex(&order_id))))?;
+    let tokens = state
+        .order_repository
+        .get_order_tokens(order_id.clone())
+        .await?;
+    let events = state
+        .order_event_repository
+        .get_order_events(order_id)
+        .await?;
+
+    let mut response = order_response(&order, &tokens, &state.live_config().token_registry)?;
+    response.events = Some(events.into_iter().map(order_event_response).collect());
+    Ok(Json(response))
+}
+
+#[derive(Serialize)]
+struct ChainProgress {
+    chain_id: u64,
+    name: String,
+    indexed_block: Option<i64>,
+    checkpointed_at: Option<DateTime<Utc>>,
+    chain_head: Option<u64>,
+    lag_blocks: Option<u64>,
+}
+
+/// Indexing progress of every configured chain, from its checkpoint and its listener.
+async fn list_chains(State(state): State<ApiState>) -> Result<Json<Vec<ChainProgress>>, ApiError> {
+    let checkpoints = state
+        .block_checkpoint_repository
+        .get_block_checkpoints()
+        .await?;
+    let services = health().services();
+
+    let chains = state
+        .live_config()
+        .chains
+        .iter()
+        .map(|chain| {
+            let checkpoint = checkpoints
+                .iter()
+                .find(|checkpoint| checkpoint.chain_id == chain.chain_id as i64);
+            let listener = services
+                .values()
+                .find(|service| service.chain == chain.name && service.chain_head.is_some());
+            ChainProgress {
+                chain_id: chain.chain_id,
+                name: chain.name.clone(),
+                indexed_block: checkpoint.map(|checkpoint| checkpoint.height),
+                checkpointed_at: checkpoint.map(|checkpoint| checkpoint.processed_at.and_utc()),
+                chain_head: listener.and_then(|listener| listener.chain_head),
+                lag_blocks: listener.and_then(|listener| listener.lag_blocks()),
+            }
+        })
+        .collect();
+    Ok(Json(chains))
+}
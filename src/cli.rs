@@ -0,0 +1,246 @@
+use alloy::primitives::{Address, Bytes, Log, B256};
+use clap::{Parser, Subcommand, ValueEnum};
+use eyre::Result;
+use migration::{Migrator, MigratorTrait};
+use sea_orm::Database;
+
+use crate::{
+    api::format::{order_event_response, order_response},
+    context::{self, AppConfig, ChainConfig},
+    listener::OrderbookEvent,
+    repository::{
+        chain_state::ChainStateRepository, order::OrderRepository,
+        order_event::OrderEventRepository,
+    },
+    schema,
+    token::TokenRegistry,
+};
+
+#[derive(Debug, Parser)]
+#[command(name = "bot", version, about = "Fills ILayer orders")]
+pub struct Cli {
+    /// Runs the bot when omitted.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Runs the services of the configured chains.
+    Run {
+        /// Only runs these chains, by name or chain id.
+        #[arg(long = "chain")]
+        chains: Vec<String>,
+        /// Only runs these services.
+        #[arg(long = "service", value_enum)]
+        services: Vec<ServiceKind>,
+    },
+    /// Applies or reverts the database migrations.
+    Migrate {
+        #[command(subcommand)]
+        command: MigrateCommand,
+    },
+    /// Asks the chain's listener to index a block range again.
+    Reindex {
+        /// Chain name or chain id.
+        #[arg(long)]
+        chain: String,
+        #[arg(long)]
+        from: u64,
+        #[arg(long)]
+        to: u64,
+    },
+    /// Inspects indexed orders.
+    Order {
+        #[command(subcommand)]
+        command: OrderCommand,
+    },
+    /// Decodes an orderbook log the way the listener does.
+    DecodeLog {
+        /// Comma separated hex topics.
+        topics: String,
+        /// Hex data of the log.
+        data: String,
+    },
+    /// Checks the configuration.
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ServiceKind {
+    Listener,
+    Filler,
+    Reconciler,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MigrateCommand {
+    /// Applies the pending migrations.
+    Up,
+    /// Reverts the last migrations.
+    Down {
+        #[arg(long, default_value_t = 1)]
+        steps: u32,
+    },
+    /// Lists the applied and pending migrations.
+    Status,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum OrderCommand {
+    /// Shows an order with its token legs and events.
+    Show {
+        /// Hex order id.
+        order_id: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Loads the configuration and reports the problems found.
+    Check,
+    /// Encrypts a TOML table of secrets with the `ILR_SECRETS_PASSPHRASE` passphrase, for
+    /// the `secrets_path` setting.
+    EncryptSecrets {
+        /// Plain text TOML secrets.
+        input: String,
+        /// Encrypted file to write.
+        output: String,
+    },
+}
+
+/// Finds a configured chain by name or chain id.
+pub fn find_chain<'a>(config: &'a AppConfig, chain: &str) -> Result<&'a ChainConfig> {
+    config
+        .chain
+        .iter()
+        .find(|chain_config| {
+            chain_config.name == chain || chain_config.chain_id.to_string() == chain
+        })
+        .ok_or(eyre::eyre!("Chain {chain} is not configured"))
+}
+
+pub async fn migrate(config: &AppConfig, command: MigrateCommand) -> Result<()> {
+    let connection = Database::connect(config.postgres_url.expose()).await?;
+    match command {
+        MigrateCommand::Up => schema::prepare(config.postgres_url.expose(), true).await?,
+        MigrateCommand::Down { steps } => Migrator::down(&connection, Some(steps)).await?,
+        MigrateCommand::Status => {
+            for migration in Migrator::get_applied_migrations(&connection).await? {
+                println!("applied  {}", migration.name());
+            }
+            for migration in Migrator::get_pending_migrations(&connection).await? {
+                println!("pending  {}", migration.name());
+            }
+        }
+    }
+    Ok(())
+}
+
+pub async fn reindex(config: &AppConfig, chain: &str, from: u64, to: u64) -> Result<()> {
+    let chain = find_chain(config, chain)?;
+    if from > to {
+        return Err(eyre::eyre!("--from {from} is after --to {to}"));
+    }
+    ChainStateRepository::new(config.postgres_url.expose().to_string())
+        .await?
+        .request_reindex(chain.chain_id, from, Some(to))
+        .await?;
+    println!(
+        "Re-index of blocks {from} to {to} requested, the {} listener picks it up on its next block",
+        chain.name
+    );
+    Ok(())
+}
+
+pub async fn show_order(config: &AppConfig, order_id: &str) -> Result<()> {
+    let order_id = hex::decode(order_id.trim_start_matches("0x"))?;
+    let order_repository = OrderRepository::new(config.postgres_url.expose().to_string()).await?;
+    let order = order_repository
+        .find_order(order_id.clone())
+        .await?
+        .ok_or(eyre::eyre!("Order not found"))?;
+    let tokens = order_repository.get_order_tokens(order_id.clone()).await?;
+    let events = OrderEventRepository::new(config.postgres_url.expose().to_string())
+        .await?
+        .get_order_events(order_id)
+        .await?;
+
+    let mut response = order_response(&order, &tokens, &TokenRegistry::new(&config.chain))?;
+    response.events = Some(events.into_iter().map(order_event_response).collect());
+    println!("{}", serde_json::to_string_pretty(&response)?);
+    Ok(())
+}
+
+/// Decodes a log from its hex topics and data, returning the event name and arguments.
+pub fn decode_log(topics: &str, data: &str) -> Result<serde_json::Value> {
+    let topics = topics
+        .split(',')
+        .map(|topic| Ok(topic.trim().parse::<B256>()?))
+        .collect::<Result<Vec<_>>>()?;
+    let data: Bytes = data.trim().parse()?;
+    let log = Log::new(Address::ZERO, topics, data).ok_or(eyre::eyre!("Too many topics"))?;
+
+    let event = OrderbookEvent::decode(&log).ok_or(eyre::eyre!("Not an orderbook event"))?;
+    Ok(serde_json::json!({
+        "event": event.name(),
+        "args": event.to_json(),
+    }))
+}
+
+/// Checks the RPC endpoints of a configuration, the rest is validated when it is loaded.
+pub async fn check_config(config: &AppConfig) -> Result<()> {
+    config.check_chain_ids().await?;
+    for chain in &config.chain {
+        println!("{} ({}): ok", chain.name, chain.chain_id);
+    }
+    Ok(())
+}
+
+pub fn encrypt_secrets(input: &str, output: &str) -> Result<()> {
+    let passphrase = std::env::var("ILR_SECRETS_PASSPHRASE")
+        .map_err(|_| eyre::eyre!("ILR_SECRETS_PASSPHRASE is not set"))?;
+    let encrypted = context::encrypt_secrets(&std::fs::read_to_string(input)?, &passphrase)?;
+    std::fs::write(output, encrypted)?;
+    println!("Secrets encrypted to {output}, {input} can be deleted");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::{Address, FixedBytes},
+        sol_types::SolEvent,
+    };
+
+    use super::decode_log;
+    use crate::solidity::Orderbook;
+
+    #[test]
+    fn test_decode_log() {
+        let event = Orderbook::OrderFilled {
+            orderId: FixedBytes::repeat_byte(0x11),
+            filler: Address::repeat_byte(0x22),
+        };
+        let log_data = event.encode_log_data();
+        let topics = log_data
+            .topics()
+            .iter()
+            .map(|topic| topic.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let decoded = decode_log(&topics, &log_data.data.to_string()).unwrap();
+        assert_eq!(decoded["event"], "OrderFilled");
+        assert_eq!(
+            decoded["args"]["filler"],
+            Address::repeat_byte(0x22).to_string()
+        );
+
+        assert!(decode_log(&topics, "0xzz").is_err());
+        assert!(decode_log(&topics[..66], "0x").is_err());
+    }
+}
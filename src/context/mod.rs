@@ -1,14 +1,24 @@
-use config::{Config, ConfigError};
+use alloy::{primitives::Address, signers::local::PrivateKeySigner};
+use config::Config;
 use eyre::Result;
 use serde::Deserialize;
 
-#[derive(Debug, Deserialize, Clone, Default)]
+use crate::alert::Severity;
+
+mod secret;
+mod validation;
+
+pub use secret::{encrypt_secrets, redact_url, Secret};
+
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
 #[allow(dead_code)]
 pub struct ChainConfig {
     pub name: String,
     pub chain_id: u64,
-    pub rpc_url: String,
-    pub ws_url: String,
+    pub rpc_url: Secret,
+    pub ws_url: Secret,
+    /// Milliseconds between two polls while the log subscription is down.
+    pub listener_poll_interval: Option<u64>,
 
     pub start_block: Option<u64>,
     pub block_batch_size: Option<u64>,
@@ -18,42 +28,298 @@ pub struct ChainConfig {
     pub profitability_threshold: f32,
 
     pub order_contract_address: String,
+    /// Multicall3 deployment, when not at its canonical address.
+    pub multicall_address: Option<String>,
+    /// Look up settlements relayed by a dispatcher contract with `debug_traceTransaction`,
+    /// which the RPC endpoints must support.
+    #[serde(default)]
+    pub trace_settlements: bool,
     pub filler_poll_interval: u64,
+    pub filler_private_key: Option<Secret>,
+
+    #[serde(default)]
+    pub tokens: Vec<TokenConfig>,
+    #[serde(default)]
+    pub limits: LimitsConfig,
+    #[serde(default)]
+    pub validation: ValidationConfig,
+    #[serde(default)]
+    pub hooks: HookConfig,
+    #[serde(default)]
+    pub messaging_fee: MessagingFeeConfig,
+    #[serde(default)]
+    pub reconciler: ReconcilerConfig,
+    #[serde(default)]
+    pub rpc: RpcConfig,
+    #[serde(default)]
+    pub checkpoint: CheckpointConfig,
+    #[serde(default)]
+    pub health: HealthConfig,
+    /// Price of the native token in notional units, used to value fill costs.
+    pub native_token_price: Option<f64>,
+}
+
+impl ChainConfig {
+    /// Endpoints of the chain, falling back to `rpc_url` and `ws_url`.
+    pub fn rpc_endpoints(&self) -> Vec<RpcEndpointConfig> {
+        if !self.rpc.endpoints.is_empty() {
+            return self.rpc.endpoints.clone();
+        }
+        vec![RpcEndpointConfig {
+            http_url: self.rpc_url.clone(),
+            ws_url: Some(self.ws_url.clone()),
+            ..Default::default()
+        }]
+    }
+
+    pub fn find_token(&self, address: Address) -> Option<&TokenConfig> {
+        self.tokens.iter().find(|token| {
+            token
+                .address
+                .parse::<Address>()
+                .is_ok_and(|token_address| token_address == address)
+        })
+    }
+}
+
+/// Spending caps enforced by the filler before each fill on this chain.
+/// Native amounts are in whole native tokens, notional amounts in the unit of the token prices.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+pub struct LimitsConfig {
+    /// Checked against the `max_fee_per_gas` of the fill transaction.
+    pub max_gas_price_gwei: Option<f64>,
+    pub max_fee_per_fill: Option<f64>,
+    pub daily_native_budget: Option<f64>,
+    pub daily_notional_cap: Option<f64>,
+}
+
+/// Rules applied to orders created on this chain before they reach the filler.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+pub struct ValidationConfig {
+    #[serde(default)]
+    pub denied_users: Vec<String>,
+    /// Minimum number of seconds left before the order deadline.
+    pub min_deadline_window: Option<u64>,
+}
+
+/// Destination calls (`callRecipient`/`callData`) the filler accepts on this chain.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+pub struct HookConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub allowed_recipients: Vec<String>,
+    /// Four byte function selectors, e.g. `"0xa9059cbb"`.
+    #[serde(default)]
+    pub allowed_selectors: Vec<String>,
+    /// Gas granted to the hook when the settler runs it.
+    pub gas_limit: Option<u64>,
+}
+
+/// How the cross-chain messaging fee of sponsored orders is priced on this chain. The fee is
+/// not quoted by the messaging layer, so the fixed fee has to cover every route to the chain.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+pub struct MessagingFeeConfig {
+    /// Fee in whole native tokens sent along the fills of sponsored orders from other chains,
+    /// required when other chains are configured. Orders within a chain pay none.
+    pub fixed_fee: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RpcStrategy {
+    /// Always use the healthiest endpoint with the lowest priority value.
+    #[default]
+    Priority,
+    /// Spread requests over all healthy endpoints.
+    RoundRobin,
+}
+
+/// One RPC provider of a chain.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+pub struct RpcEndpointConfig {
+    pub http_url: Secret,
+    pub ws_url: Option<Secret>,
+    /// Lower values are tried first by the priority strategy.
+    #[serde(default)]
+    pub priority: u32,
+    pub max_requests_per_second: Option<u32>,
+}
+
+/// Extra endpoints and failover policy, `rpc_url` and `ws_url` are used when no endpoints
+/// are listed.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+pub struct RpcConfig {
+    #[serde(default)]
+    pub endpoints: Vec<RpcEndpointConfig>,
+    #[serde(default)]
+    pub strategy: RpcStrategy,
+    /// Number of endpoints that must have reached a block before it is considered the head.
+    pub quorum: Option<usize>,
+    /// Blocks an endpoint can fall behind the head before it is taken out of rotation.
+    pub max_block_lag: Option<u64>,
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+/// How often the listener persists its position while following new heads.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+pub struct CheckpointConfig {
+    /// Blocks between two checkpoint writes.
+    pub interval_blocks: Option<u64>,
+    /// Seconds after which the checkpoint is written anyway.
+    pub interval_seconds: Option<u64>,
+    /// Blocks behind the chain head the checkpoint stays, waiting for logs the subscription
+    /// delivers after the head and indexing reorged blocks again after a restart. Defaults to 2.
+    pub confirmations: Option<u64>,
+}
+
+/// Thresholds of the `/healthz` and `/readyz` endpoints for the chain's services.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+pub struct HealthConfig {
+    /// Blocks the listener may lag behind the chain head before it is unready.
+    pub max_lag_blocks: Option<u64>,
+    /// Seconds without a new chain head before the listener is unready.
+    pub max_head_age: Option<u64>,
+    /// Errors in a row before a service is reported as not live.
+    pub max_consecutive_errors: Option<u32>,
+}
+
+/// Periodic check of the indexed order statuses against the orderbook.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+pub struct ReconcilerConfig {
+    /// Milliseconds between two reconciliation passes.
+    pub interval: Option<u64>,
+    /// Number of orders whose status is read in a single batch.
+    pub batch_size: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
 #[allow(dead_code)]
 pub struct TokenConfig {
     pub name: String,
+    /// Read from the token contract when empty.
+    #[serde(default)]
     pub symbol: String,
     #[serde(rename = "type")]
     pub token_type: String,
     pub address: String,
+    /// Read from the token contract when missing.
     pub decimals: Option<u8>,
-    pub price_feed: String,
+    /// Chainlink aggregator giving the price of one whole token in notional units.
+    pub price_feed: Option<String>,
+    /// Price of one whole token in notional units, used when there is no `price_feed`.
+    pub price: Option<f64>,
     pub display_decimals: Option<u8>,
     pub image: String,
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
 pub struct AppConfig {
     pub chain: Vec<ChainConfig>,
-    pub postgres_url: String,
+    pub postgres_url: Secret,
+    /// Applies the pending database migrations on startup.
+    #[serde(default)]
+    pub auto_migrate: bool,
+    #[serde(default)]
+    pub server: ServerConfig,
+    #[serde(default)]
+    pub log: LogConfig,
+    #[serde(default)]
+    pub alerts: AlertsConfig,
+}
+
+/// HTTP server exposing the metrics and health endpoints.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+pub struct ServerConfig {
+    /// Defaults to `0.0.0.0:9090`.
+    pub listen_address: Option<String>,
+    /// Bearer token of the `/api/admin` routes, which are disabled without it.
+    pub admin_token: Option<Secret>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    /// One JSON object per line, with the fields of the enclosing spans.
+    Json,
+}
+
+/// Log output, and export of the tracing spans to an OpenTelemetry collector.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+pub struct LogConfig {
+    #[serde(default)]
+    pub format: LogFormat,
+    /// OTLP/HTTP traces endpoint, e.g. `http://localhost:4318/v1/traces`. Spans are only
+    /// exported when set.
+    pub otlp_endpoint: Option<String>,
+    /// Defaults to `ilayer-bot`.
+    pub service_name: Option<String>,
+}
+
+/// Notifications of the alerts raised by the services.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+pub struct AlertsConfig {
+    /// Seconds during which an alert of the same kind on the same chain is sent once.
+    pub dedup_window: Option<u64>,
+    /// Notifications a sink sends per minute at most, the others are dropped.
+    pub max_per_minute: Option<usize>,
+    #[serde(default)]
+    pub sinks: Vec<AlertSinkConfig>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct AlertSinkConfig {
+    #[serde(flatten)]
+    pub notifier: NotifierConfig,
+    /// Defaults to `warning`.
+    pub min_severity: Option<Severity>,
+    /// `{severity}`, `{kind}`, `{chain}` and `{message}` are replaced by the alert fields.
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotifierConfig {
+    /// JSON POST of the alert fields.
+    Webhook { url: Secret },
+    /// Slack incoming webhook, or any webhook taking a `text` field.
+    Slack { url: Secret },
+    Telegram {
+        bot_token: Secret,
+        chat_id: String,
+        /// Defaults to `https://api.telegram.org`.
+        api_url: Option<String>,
+    },
+}
+
+impl AppConfig {
+    /// Addresses the bot fills from, derived from the configured filler keys.
+    pub fn filler_addresses(&self) -> Result<Vec<Address>> {
+        self.chain
+            .iter()
+            .filter_map(|chain| chain.filler_private_key.as_ref())
+            .map(|private_key| Ok(private_key.expose().parse::<PrivateKeySigner>()?.address()))
+            .collect()
+    }
 }
 
 pub struct AppContext {
     pub config: AppConfig,
 }
 
-pub fn config() -> Result<AppConfig, ConfigError> {
-    let settings = Config::builder()
+pub fn config() -> Result<AppConfig> {
+    let mut settings = Config::builder()
         .add_source(config::File::with_name("./config.toml").required(false))
         .add_source(config::Environment::with_prefix("ILR"))
         .build()?;
+    secret::resolve_secrets(&mut settings.cache)?;
 
-    settings.try_deserialize::<AppConfig>()
+    Ok(settings.cache.try_deserialize::<AppConfig>()?)
 }
 
 pub fn context() -> Result<AppContext> {
-    Ok(AppContext { config: config()? })
+    let config = config()?;
+    config.validate()?;
+    Ok(AppContext { config })
 }
@@ -0,0 +1,251 @@
+use alloy::{
+    primitives::{Address, Bytes, FixedBytes},
+    providers::Provider,
+    rpc::{json_rpc::ErrorPayload, types::TransactionRequest},
+};
+use eyre::Result;
+
+use super::validation::RejectionReason;
+use crate::{
+    context::HookConfig,
+    rpc::RpcPool,
+    solidity::{bytes64_to_address, bytes64_to_vec, Validator},
+};
+
+const DEFAULT_HOOK_GAS_LIMIT: u64 = 200_000;
+/// JSON-RPC error code of a call that reverted with revert data.
+const EXECUTION_REVERTED: i64 = 3;
+
+/// The destination call an order asks the settler to make once it is filled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hook {
+    pub recipient: Address,
+    pub selector: Option<FixedBytes<4>>,
+    pub call_data: Bytes,
+}
+
+pub enum HookSimulation {
+    Succeeded,
+    Reverted(String),
+}
+
+impl HookConfig {
+    pub fn gas_limit(&self) -> u64 {
+        self.gas_limit.unwrap_or(DEFAULT_HOOK_GAS_LIMIT)
+    }
+}
+
+/// Decodes `callRecipient`/`callData`, returns `None` for orders without a hook.
+pub fn decode_hook(order: &Validator::Order) -> Result<Option<Hook>, RejectionReason> {
+    let call_recipient = bytes64_to_vec(&order.callRecipient);
+    let has_call_recipient = call_recipient.iter().any(|b| *b != 0);
+    if !has_call_recipient && order.callData.is_empty() {
+        return Ok(None);
+    }
+
+    let recipient = bytes64_to_address(&call_recipient)
+        .filter(|recipient| !recipient.is_zero())
+        .ok_or(RejectionReason::InvalidHook)?;
+    let selector = match order.callData.len() {
+        0 => None,
+        1..4 => return Err(RejectionReason::InvalidHook),
+        _ => Some(FixedBytes::from_slice(&order.callData[..4])),
+    };
+
+    Ok(Some(Hook {
+        recipient,
+        selector,
+        call_data: order.callData.clone(),
+    }))
+}
+
+pub fn check_hook_policy(config: &HookConfig, hook: &Hook) -> Result<(), RejectionReason> {
+    if !config.enabled {
+        return Err(RejectionReason::HooksDisabled);
+    }
+
+    let recipient_allowed = config.allowed_recipients.iter().any(|recipient| {
+        recipient
+            .parse::<Address>()
+            .is_ok_and(|recipient| recipient == hook.recipient)
+    });
+    if !recipient_allowed {
+        return Err(RejectionReason::HookRecipientNotAllowed);
+    }
+
+    if let Some(selector) = hook.selector {
+        let selector_allowed = config.allowed_selectors.iter().any(|allowed| {
+            allowed
+                .parse::<FixedBytes<4>>()
+                .is_ok_and(|allowed| allowed == selector)
+        });
+        if !selector_allowed {
+            return Err(RejectionReason::HookSelectorNotAllowed);
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether the node answered that the call reverted, rather than failing to run it.
+fn is_revert(payload: &ErrorPayload) -> bool {
+    payload.code == EXECUTION_REVERTED
+        || payload.data.is_some()
+        || payload.message.contains("execution reverted")
+}
+
+/// Runs the fill of an order with a hook, so the hook sees the outputs the settler delivers
+/// before calling it. Errors other than a revert are returned, the order is tried again.
+pub async fn simulate_fill(
+    rpc_pool: &RpcPool,
+    fill: &TransactionRequest,
+) -> Result<HookSimulation> {
+    let result = rpc_pool
+        .call(|provider| async move { provider.call(fill).await })
+        .await;
+    match result {
+        Ok(_) => Ok(HookSimulation::Succeeded),
+        Err(e) => match e.as_error_resp() {
+            Some(payload) if is_revert(payload) => {
+                Ok(HookSimulation::Reverted(payload.message.to_string()))
+            }
+            _ => Err(e.into()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::{Address, FixedBytes},
+        rpc::json_rpc::ErrorPayload,
+    };
+    use serde_json::value::RawValue;
+
+    use super::{check_hook_policy, decode_hook, is_revert, Hook};
+    use crate::{
+        context::HookConfig,
+        filler::validation::RejectionReason,
+        solidity::{_bytes64, Validator},
+    };
+
+    const RECIPIENT: &str = "0x23618e81E3f5cdF7f54C3d65f7FBc0aBf5B21E8f";
+
+    fn order(call_recipient: Option<&str>, call_data: &[u8]) -> Validator::Order {
+        let call_recipient = call_recipient
+            .map(|recipient| recipient.parse::<Address>().unwrap().into_word())
+            .unwrap_or_default();
+        Validator::Order {
+            user: _bytes64 {
+                lower: FixedBytes::ZERO,
+                upper: FixedBytes::ZERO,
+            },
+            filler: _bytes64 {
+                lower: FixedBytes::ZERO,
+                upper: FixedBytes::ZERO,
+            },
+            inputs: vec![],
+            outputs: vec![],
+            sourceChainSelector: Default::default(),
+            destinationChainSelector: Default::default(),
+            sponsored: false,
+            primaryFillerDeadline: Default::default(),
+            deadline: Default::default(),
+            callRecipient: _bytes64 {
+                lower: call_recipient,
+                upper: FixedBytes::ZERO,
+            },
+            callData: call_data.to_vec().into(),
+        }
+    }
+
+    fn config() -> HookConfig {
+        HookConfig {
+            enabled: true,
+            allowed_recipients: vec![RECIPIENT.to_string()],
+            allowed_selectors: vec!["0xa9059cbb".to_string()],
+            gas_limit: Some(100_000),
+        }
+    }
+
+    #[test]
+    fn test_decode_hook() {
+        assert_eq!(decode_hook(&order(None, &[])), Ok(None));
+
+        let hook = decode_hook(&order(Some(RECIPIENT), &[0xa9, 0x05, 0x9c, 0xbb, 0x01]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(hook.recipient, RECIPIENT.parse::<Address>().unwrap());
+        assert_eq!(
+            hook.selector,
+            Some(FixedBytes::new([0xa9, 0x05, 0x9c, 0xbb]))
+        );
+
+        assert_eq!(
+            decode_hook(&order(None, &[0xa9, 0x05, 0x9c, 0xbb])),
+            Err(RejectionReason::InvalidHook)
+        );
+        assert_eq!(
+            decode_hook(&order(Some(RECIPIENT), &[0xa9, 0x05])),
+            Err(RejectionReason::InvalidHook)
+        );
+    }
+
+    #[test]
+    fn test_is_revert() {
+        let error = |code: i64, message: &str, data: Option<&str>| ErrorPayload {
+            code,
+            message: message.to_string().into(),
+            data: data.map(|data| RawValue::from_string(data.to_string()).unwrap()),
+        };
+        assert!(is_revert(&error(
+            3,
+            "execution reverted: hook failed",
+            Some("\"0x08c379a0\"")
+        )));
+        assert!(is_revert(&error(-32000, "execution reverted", None)));
+        assert!(!is_revert(&error(-32000, "header not found", None)));
+        assert!(!is_revert(&error(429, "rate limit exceeded", None)));
+        assert!(!is_revert(&error(-32603, "internal error", None)));
+    }
+
+    #[test]
+    fn test_hook_policy() {
+        let hook = Hook {
+            recipient: RECIPIENT.parse().unwrap(),
+            selector: Some(FixedBytes::new([0xa9, 0x05, 0x9c, 0xbb])),
+            call_data: vec![0xa9, 0x05, 0x9c, 0xbb].into(),
+        };
+        assert_eq!(check_hook_policy(&config(), &hook), Ok(()));
+
+        assert_eq!(
+            check_hook_policy(&HookConfig::default(), &hook),
+            Err(RejectionReason::HooksDisabled)
+        );
+
+        let other_recipient = Hook {
+            recipient: Address::repeat_byte(1),
+            ..hook.clone()
+        };
+        assert_eq!(
+            check_hook_policy(&config(), &other_recipient),
+            Err(RejectionReason::HookRecipientNotAllowed)
+        );
+
+        let other_selector = Hook {
+            selector: Some(FixedBytes::new([0x09, 0x5e, 0xa7, 0xb3])),
+            ..hook.clone()
+        };
+        assert_eq!(
+            check_hook_policy(&config(), &other_selector),
+            Err(RejectionReason::HookSelectorNotAllowed)
+        );
+
+        let no_call_data = Hook {
+            selector: None,
+            call_data: Default::default(),
+            ..hook
+        };
+        assert_eq!(check_hook_policy(&config(), &no_call_data), Ok(()));
+    }
+}
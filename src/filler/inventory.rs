@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+
+use alloy::primitives::{Address, U256};
+use eyre::Result;
+
+use crate::{
+    multicall::{aggregate, Multicall},
+    rpc::RpcPool,
+    solidity::{bytes64_to_address, bytes64_to_vec, Validator, ERC20},
+};
+
+/// A token the filler does not hold enough of to pay the order outputs.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Shortfall {
+    pub token: Address,
+    pub balance: U256,
+    pub required: U256,
+}
+
+/// Amount of each token the outputs add up to.
+fn required_amounts(outputs: &[Validator::Token]) -> Result<BTreeMap<Address, U256>> {
+    let mut required = BTreeMap::<Address, U256>::new();
+    for output in outputs {
+        let token = bytes64_to_address(&bytes64_to_vec(&output.tokenAddress))
+            .ok_or(eyre::eyre!("Output token is not an EVM address"))?;
+        let amount = required.entry(token).or_default();
+        *amount = amount.saturating_add(output.amount);
+    }
+    Ok(required)
+}
+
+/// ERC20 balances of the filler that are below what the order outputs need, the balances
+/// are read in a single multicall.
+pub async fn inventory_shortfalls(
+    rpc_pool: &RpcPool,
+    multicall_address: Option<Address>,
+    filler: Address,
+    outputs: &[Validator::Token],
+) -> Result<Vec<Shortfall>> {
+    let required = required_amounts(outputs)?;
+    let balances = rpc_pool
+        .call(|provider| {
+            let tokens = required.keys();
+            async move {
+                let mut multicall = Multicall::new(&provider);
+                if let Some(multicall_address) = multicall_address {
+                    multicall = multicall.with_address(multicall_address);
+                }
+                let calls = tokens.map(|token| (*token, ERC20::balanceOfCall { account: filler }));
+                aggregate(multicall, calls).await
+            }
+        })
+        .await?;
+
+    let mut shortfalls = vec![];
+    for ((token, required), balance) in required.into_iter().zip(balances) {
+        let balance = balance
+            .map_err(|e| eyre::eyre!("Unable to read the balance of token {token}: {e:?}"))?
+            ._0;
+        if balance < required {
+            shortfalls.push(Shortfall {
+                token,
+                balance,
+                required,
+            });
+        }
+    }
+    Ok(shortfalls)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{address, U256};
+
+    use super::required_amounts;
+    use crate::solidity::{address_to_bytes64_vec, vec_to_bytes64, Validator};
+
+    #[test]
+    fn test_required_amounts() {
+        let usdc = address!("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48");
+        let weth = address!("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2");
+        let token = |address, amount| Validator::Token {
+            tokenAddress: vec_to_bytes64(&address_to_bytes64_vec(address)).unwrap(),
+            tokenId: U256::MAX,
+            amount: U256::from(amount),
+        };
+
+        let required =
+            required_amounts(&[token(usdc, 100), token(weth, 1), token(usdc, 50)]).unwrap();
+        assert_eq!(required[&usdc], U256::from(150));
+        assert_eq!(required[&weth], U256::from(1));
+    }
+}
@@ -0,0 +1,244 @@
+use std::fmt;
+
+use entity::spending_counter;
+
+use crate::context::LimitsConfig;
+
+const WEI_PER_GWEI: f64 = 1e9;
+const WEI_PER_NATIVE: f64 = 1e18;
+
+pub fn wei_to_native(wei: u128) -> f64 {
+    wei as f64 / WEI_PER_NATIVE
+}
+
+pub fn native_to_wei(native: f64) -> u128 {
+    (native * WEI_PER_NATIVE) as u128
+}
+
+/// What a fill is expected to cost, evaluated against the configured caps.
+#[derive(Debug, Clone, Copy)]
+pub struct FillCost {
+    /// EIP-1559 fee cap of the fill transaction, the most it can pay per gas.
+    pub max_fee_per_gas: u128,
+    pub fee: f64,
+    pub notional: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LimitBreach {
+    GasPrice {
+        gas_price_gwei: f64,
+        max: f64,
+    },
+    FeePerFill {
+        fee: f64,
+        max: f64,
+    },
+    DailyNativeBudget {
+        spent: f64,
+        fee: f64,
+        budget: f64,
+    },
+    DailyNotionalCap {
+        filled: f64,
+        notional: f64,
+        cap: f64,
+    },
+}
+
+impl LimitBreach {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            LimitBreach::GasPrice { .. } => "max_gas_price",
+            LimitBreach::FeePerFill { .. } => "max_fee_per_fill",
+            LimitBreach::DailyNativeBudget { .. } => "daily_native_budget",
+            LimitBreach::DailyNotionalCap { .. } => "daily_notional_cap",
+        }
+    }
+}
+
+impl fmt::Display for LimitBreach {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitBreach::GasPrice {
+                gas_price_gwei,
+                max,
+            } => write!(f, "max fee per gas {gas_price_gwei} gwei exceeds {max} gwei"),
+            LimitBreach::FeePerFill { fee, max } => {
+                write!(f, "fill fee {fee} exceeds the per fill cap of {max}")
+            }
+            LimitBreach::DailyNativeBudget { spent, fee, budget } => write!(
+                f,
+                "fill fee {fee} on top of {spent} spent today exceeds the daily budget of {budget}"
+            ),
+            LimitBreach::DailyNotionalCap {
+                filled,
+                notional,
+                cap,
+            } => write!(
+                f,
+                "fill notional {notional} on top of {filled} filled today exceeds the daily cap of {cap}"
+            ),
+        }
+    }
+}
+
+pub struct SpendingLimits<'a> {
+    config: &'a LimitsConfig,
+}
+
+impl<'a> SpendingLimits<'a> {
+    pub fn new(config: &'a LimitsConfig) -> Self {
+        Self { config }
+    }
+
+    /// Checks a fill against the caps, given what has already been spent today.
+    pub fn check(
+        &self,
+        cost: &FillCost,
+        today: Option<&spending_counter::Model>,
+    ) -> Result<(), LimitBreach> {
+        let gas_price_gwei = cost.max_fee_per_gas as f64 / WEI_PER_GWEI;
+        if let Some(max) = self.config.max_gas_price_gwei {
+            if gas_price_gwei > max {
+                return Err(LimitBreach::GasPrice {
+                    gas_price_gwei,
+                    max,
+                });
+            }
+        }
+
+        if let Some(max) = self.config.max_fee_per_fill {
+            if cost.fee > max {
+                return Err(LimitBreach::FeePerFill { fee: cost.fee, max });
+            }
+        }
+
+        let spent = today.map_or(0.0, |counter| counter.native_spent);
+        if let Some(budget) = self.config.daily_native_budget {
+            if spent + cost.fee > budget {
+                return Err(LimitBreach::DailyNativeBudget {
+                    spent,
+                    fee: cost.fee,
+                    budget,
+                });
+            }
+        }
+
+        let filled = today.map_or(0.0, |counter| counter.notional_filled);
+        if let Some(cap) = self.config.daily_notional_cap {
+            if filled + cost.notional > cap {
+                return Err(LimitBreach::DailyNotionalCap {
+                    filled,
+                    notional: cost.notional,
+                    cap,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FillCost, LimitBreach, SpendingLimits};
+    use crate::context::LimitsConfig;
+    use entity::spending_counter;
+
+    fn limits() -> LimitsConfig {
+        LimitsConfig {
+            max_gas_price_gwei: Some(50.0),
+            max_fee_per_fill: Some(0.01),
+            daily_native_budget: Some(0.05),
+            daily_notional_cap: Some(10_000.0),
+        }
+    }
+
+    fn spent_today(native_spent: f64, notional_filled: f64) -> spending_counter::Model {
+        spending_counter::Model {
+            id: 1,
+            chain_id: 1,
+            day: chrono::Utc::now().date_naive(),
+            native_spent,
+            notional_filled,
+            fill_count: 1,
+            updated_at: chrono::Utc::now().naive_utc(),
+        }
+    }
+
+    #[test]
+    fn test_fill_within_limits() {
+        let config = limits();
+        let cost = FillCost {
+            max_fee_per_gas: 20_000_000_000,
+            fee: 0.005,
+            notional: 1_000.0,
+        };
+
+        assert_eq!(SpendingLimits::new(&config).check(&cost, None), Ok(()));
+        assert_eq!(
+            SpendingLimits::new(&config).check(&cost, Some(&spent_today(0.04, 8_000.0))),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_fill_breaches_limits() {
+        let config = limits();
+        let limits = SpendingLimits::new(&config);
+
+        let expensive_gas = FillCost {
+            max_fee_per_gas: 51_000_000_000,
+            fee: 0.005,
+            notional: 1_000.0,
+        };
+        assert_eq!(
+            limits.check(&expensive_gas, None).unwrap_err().kind(),
+            "max_gas_price"
+        );
+
+        let expensive_fill = FillCost {
+            max_fee_per_gas: 20_000_000_000,
+            fee: 0.02,
+            notional: 1_000.0,
+        };
+        assert_eq!(
+            limits.check(&expensive_fill, None).unwrap_err().kind(),
+            "max_fee_per_fill"
+        );
+
+        let cost = FillCost {
+            max_fee_per_gas: 20_000_000_000,
+            fee: 0.005,
+            notional: 1_000.0,
+        };
+        assert_eq!(
+            limits.check(&cost, Some(&spent_today(0.048, 0.0))),
+            Err(LimitBreach::DailyNativeBudget {
+                spent: 0.048,
+                fee: 0.005,
+                budget: 0.05
+            })
+        );
+        assert_eq!(
+            limits
+                .check(&cost, Some(&spent_today(0.0, 9_500.0)))
+                .unwrap_err()
+                .kind(),
+            "daily_notional_cap"
+        );
+    }
+
+    #[test]
+    fn test_no_limits_configured() {
+        let config = LimitsConfig::default();
+        let cost = FillCost {
+            max_fee_per_gas: u128::MAX,
+            fee: f64::MAX,
+            notional: f64::MAX,
+        };
+
+        assert_eq!(SpendingLimits::new(&config).check(&cost, None), Ok(()));
+    }
+}
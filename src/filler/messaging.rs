@@ -0,0 +1,49 @@
+use alloy::primitives::U256;
+use eyre::Result;
+
+use super::limits::native_to_wei;
+use crate::context::ChainConfig;
+
+/// Fee the settler has to forward to the router to send the settlement message back to
+/// the source chain, none when the order does not leave its chain. The fee is not quoted by
+/// the messaging layer, it is the one configured on the destination.
+pub fn messaging_fee(source: &ChainConfig, destination: &ChainConfig) -> Result<U256> {
+    if source.chain_id == destination.chain_id {
+        return Ok(U256::ZERO);
+    }
+    let fixed_fee = destination.messaging_fee.fixed_fee.ok_or(eyre::eyre!(
+        "No messaging_fee.fixed_fee configured for chain {}",
+        destination.name
+    ))?;
+    Ok(U256::from(native_to_wei(fixed_fee)))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::U256;
+
+    use super::messaging_fee;
+    use crate::context::{ChainConfig, MessagingFeeConfig};
+
+    fn chain(chain_id: u64, fixed_fee: Option<f64>) -> ChainConfig {
+        ChainConfig {
+            name: format!("chain-{chain_id}"),
+            chain_id,
+            messaging_fee: MessagingFeeConfig { fixed_fee },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_messaging_fee() {
+        assert_eq!(
+            messaging_fee(&chain(1, None), &chain(2, Some(0.0005))).unwrap(),
+            U256::from(500_000_000_000_000u64)
+        );
+        assert_eq!(
+            messaging_fee(&chain(1, None), &chain(1, None)).unwrap(),
+            U256::ZERO
+        );
+        assert!(messaging_fee(&chain(2, Some(0.0005)), &chain(1, None)).is_err());
+    }
+}
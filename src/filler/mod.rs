@@ -1,52 +1,792 @@
+mod hooks;
+mod inventory;
+mod limits;
+mod messaging;
+mod profitability;
+mod validation;
+
 use std::sync::Arc;
 
+use alloy::{
+    eips::BlockId,
+    network::EthereumWallet,
+    primitives::{Address, FixedBytes, TxHash, U256},
+    providers::{Provider, ProviderBuilder},
+    rpc::types::{TransactionReceipt, TransactionRequest},
+    signers::local::PrivateKeySigner,
+    sol_types::SolCall,
+};
+use entity::{order, pending_fill};
 use eyre::Result;
-use tracing::info;
+use hooks::{simulate_fill, Hook, HookSimulation};
+use inventory::inventory_shortfalls;
+use limits::{wei_to_native, FillCost, LimitBreach, SpendingLimits};
+use messaging::messaging_fee;
+use profitability::{quote_fill, tokens_notional, TokenPrices};
+use sea_orm::ActiveValue::Set;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, field, info, info_span, warn, Instrument, Span};
+use validation::validate_order;
+pub(crate) use validation::RejectionReason;
+
+use crate::{
+    alert::{self, Alert, Severity},
+    context::ChainConfig,
+    metrics::metrics,
+    repository::{
+        chain_state::ChainStateRepository,
+        contract_config::{settler_key, ContractConfigRepository},
+        order::OrderRepository,
+        spending_counter::SpendingCounterRepository,
+    },
+    runtime::{LiveConfig, LiveConfigReceiver},
+    service::Service,
+    solidity::{
+        address_to_bytes64_vec, chain_selector_to_chain_id, map_model_to_solidity_order,
+        settler::Settler, Orderbook, Validator,
+    },
+    telemetry::order_root_span,
+};
+
+/// Seconds after which a pending fill whose transaction is unknown to the node is released.
+const DROPPED_FILL_TIMEOUT: i64 = 600;
 
-use crate::{context::ChainConfig, repository::order::OrderRepository, service::Service};
+/// An order that passed validation, along with the chains it goes between.
+struct ValidOrder<'a> {
+    source: &'a ChainConfig,
+    destination: &'a ChainConfig,
+    order: Validator::Order,
+    hook: Option<Hook>,
+}
 
 pub(crate) struct Filler {
     chain_config: ChainConfig,
+    live_config: LiveConfigReceiver,
     order_repository: Arc<OrderRepository>,
+    spending_counter_repository: Arc<SpendingCounterRepository>,
+    contract_config_repository: Arc<ContractConfigRepository>,
+    chain_state_repository: Arc<ChainStateRepository>,
+    shutdown: CancellationToken,
 }
 
 impl Filler {
-    pub async fn new(postgres_url: String, chain_config: ChainConfig) -> Result<Self> {
-        let order_repository = Arc::new(OrderRepository::new(postgres_url).await?);
+    pub async fn new(
+        postgres_url: String,
+        chain_config: ChainConfig,
+        live_config: LiveConfigReceiver,
+        shutdown: CancellationToken,
+    ) -> Result<Self> {
+        let order_repository = Arc::new(OrderRepository::new(postgres_url.clone()).await?);
+        let spending_counter_repository =
+            Arc::new(SpendingCounterRepository::new(postgres_url.clone()).await?);
+        let contract_config_repository =
+            Arc::new(ContractConfigRepository::new(postgres_url.clone()).await?);
+        let chain_state_repository = Arc::new(ChainStateRepository::new(postgres_url).await?);
         Ok(Self {
             chain_config,
+            live_config,
             order_repository,
+            spending_counter_repository,
+            contract_config_repository,
+            chain_state_repository,
+            shutdown,
         })
     }
+
+    fn live_config(&self) -> Arc<LiveConfig> {
+        self.live_config.borrow().clone()
+    }
+
+    /// Validation stage between `get_ready_orders` and filling. Rejected orders get their
+    /// reason code persisted so they are not picked up again.
+    async fn validate_ready_order<'a>(
+        &self,
+        live_config: &'a LiveConfig,
+        order: &order::Model,
+    ) -> Result<Option<ValidOrder<'a>>> {
+        let Some(source) = live_config.chain(self.chain_config.chain_id) else {
+            return Ok(None);
+        };
+        let destination = match chain_selector_to_chain_id(&order.destination_chain_selector) {
+            Ok(destination_chain_id) => match live_config.chain(destination_chain_id) {
+                Some(destination) => destination,
+                // The chain may be added by a later reload, so the order is left ready
+                None => {
+                    debug!(
+                        order_id = hex::encode(&order.order_id),
+                        destination_chain_id, "Destination chain not configured, skipping order"
+                    );
+                    return Ok(None);
+                }
+            },
+            Err(_) => {
+                self.reject_invalid_order(order, RejectionReason::UnsupportedDestination)
+                    .await?;
+                return Ok(None);
+            }
+        };
+
+        let tokens = self
+            .order_repository
+            .get_order_tokens(order.order_id.clone())
+            .await?;
+        let solidity_order = map_model_to_solidity_order(order, &tokens)?;
+
+        let now = chrono::Utc::now().timestamp();
+        match validate_order(source, destination, &solidity_order, now) {
+            Ok(hook) => Ok(Some(ValidOrder {
+                source,
+                destination,
+                order: solidity_order,
+                hook,
+            })),
+            Err(reason) => {
+                self.reject_invalid_order(order, reason).await?;
+                Ok(None)
+            }
+        }
+    }
+
+    async fn reject_invalid_order(
+        &self,
+        order: &order::Model,
+        reason: RejectionReason,
+    ) -> Result<()> {
+        info!(
+            order_id = hex::encode(&order.order_id),
+            %reason,
+            "Order rejected"
+        );
+        self.order_repository
+            .reject_order(order.order_id.clone(), reason.code())
+            .await
+    }
+
+    async fn process_ready_order(&self, live_config: &LiveConfig, order: &order::Model) {
+        info!(
+            order_id = hex::encode(&order.order_id),
+            "Trying to fill ready order",
+        );
+        let valid_order = match self.validate_ready_order(live_config, order).await {
+            Ok(Some(valid_order)) => valid_order,
+            Ok(None) => return,
+            Err(e) => {
+                warn!(
+                    order_id = hex::encode(&order.order_id),
+                    error = %e,
+                    "Unable to validate order"
+                );
+                return;
+            }
+        };
+        metrics()
+            .fill_attempts
+            .with_label_values(&[&self.chain_config.name])
+            .inc();
+        if let Err(e) = self.fill_order(live_config, order, valid_order).await {
+            warn!(
+                order_id = hex::encode(&order.order_id),
+                error = %e,
+                "Unable to fill order"
+            );
+            self.count_fill_failure("error");
+        }
+    }
+
+    fn count_fill_failure(&self, reason: &str) {
+        metrics()
+            .fill_failures
+            .with_label_values(&[&self.chain_config.name, reason])
+            .inc();
+    }
+
+    /// Reports the native balance of the chain's filler wallet, failures are only logged.
+    async fn update_wallet_balance(&self, live_config: &LiveConfig, chain: &ChainConfig) {
+        let (Some(private_key), Some(rpc_pool)) = (
+            &chain.filler_private_key,
+            live_config.rpc_pools.get(&chain.chain_id),
+        ) else {
+            return;
+        };
+        let Ok(signer) = private_key.expose().parse::<PrivateKeySigner>() else {
+            return;
+        };
+        let address = signer.address();
+        match rpc_pool
+            .call(|provider| async move { provider.get_balance(address).await })
+            .await
+        {
+            Ok(balance) => metrics()
+                .wallet_balance
+                .with_label_values(&[&chain.name, &address.to_string()])
+                .set(wei_to_native(balance.try_into().unwrap_or(u128::MAX))),
+            Err(e) => warn!(
+                chain_name = chain.name,
+                %address,
+                error = %e,
+                "Unable to read the filler wallet balance"
+            ),
+        }
+    }
+
+    /// Reserves the cost of a fill on the destination chain's daily counters, which also marks
+    /// the order as being filled.
+    async fn reserve_spending(
+        &self,
+        order: &order::Model,
+        destination: &ChainConfig,
+        cost: &FillCost,
+        messaging_fee: f64,
+    ) -> Result<Result<pending_fill::Model, LimitBreach>> {
+        let limits = SpendingLimits::new(&destination.limits);
+        let today = chrono::Utc::now().date_naive();
+        loop {
+            let spent_today = self
+                .spending_counter_repository
+                .get_spending(destination.chain_id, today)
+                .await?;
+            if let Err(breach) = limits.check(cost, spent_today.as_ref()) {
+                return Ok(Err(breach));
+            }
+            let pending_fill = pending_fill::ActiveModel {
+                order_id: Set(order.order_id.clone()),
+                chain_id: Set(self.chain_config.chain_id as i64),
+                destination_chain_id: Set(destination.chain_id as i64),
+                day: Set(today),
+                reserved_fee: Set(cost.fee),
+                reserved_notional: Set(cost.notional),
+                messaging_fee: Set(messaging_fee),
+                created_at: Set(chrono::Utc::now().naive_utc()),
+                ..Default::default()
+            };
+            // When another fill reserved in between, the counters are read again
+            if let Some(pending_fill) = self
+                .spending_counter_repository
+                .reserve_fill(
+                    pending_fill,
+                    destination.limits.daily_native_budget,
+                    destination.limits.daily_notional_cap,
+                )
+                .await?
+            {
+                return Ok(Ok(pending_fill));
+            }
+        }
+    }
+
+    /// Replaces the reservation of a fill with what its transaction spent. Returns the fee
+    /// paid when the order got filled, `None` when the transaction reverted.
+    async fn settle_fill(
+        &self,
+        live_config: &LiveConfig,
+        destination: &ChainConfig,
+        pending_fill: &pending_fill::Model,
+        receipt: &TransactionReceipt,
+    ) -> Result<Option<f64>> {
+        let order_id = hex::encode(&pending_fill.order_id);
+        // The messaging fee is only kept by the router if the fill went through
+        let gas_fee = wei_to_native(receipt.gas_used * receipt.effective_gas_price);
+        let (fee, filled) = if receipt.status() {
+            (
+                gas_fee + pending_fill.messaging_fee,
+                pending_fill.reserved_notional,
+            )
+        } else {
+            (gas_fee, 0.0)
+        };
+        self.spending_counter_repository
+            .settle_fill(pending_fill, fee, filled, receipt.status())
+            .await?;
+        metrics()
+            .gas_spent
+            .with_label_values(&[&destination.name])
+            .inc_by(gas_fee);
+        self.update_wallet_balance(live_config, destination).await;
+
+        if !receipt.status() {
+            warn!(
+                order_id,
+                tx_hash = %receipt.transaction_hash,
+                "Fill transaction reverted"
+            );
+            alert::raise(
+                Alert::new(
+                    Severity::Critical,
+                    "fill_reverted",
+                    &destination.name,
+                    format!(
+                        "Fill of order {order_id} reverted in {}, {fee} native tokens spent",
+                        receipt.transaction_hash
+                    ),
+                )
+                .with_dedup_key(order_id.clone()),
+            );
+            self.count_fill_failure("reverted");
+            return Ok(None);
+        }
+        metrics()
+            .fill_successes
+            .with_label_values(&[&self.chain_config.name])
+            .inc();
+        Ok(Some(fee))
+    }
+
+    /// Settles the fills whose receipt was not awaited, because the RPC failed or the bot
+    /// stopped in between. Fills whose transaction is gone get their reservation released,
+    /// so the order is filled again.
+    async fn settle_pending_fills(&self, live_config: &LiveConfig) -> Result<()> {
+        let pending_fills = self
+            .spending_counter_repository
+            .get_pending_fills(self.chain_config.chain_id)
+            .await?;
+        for pending_fill in pending_fills {
+            let order_id = hex::encode(&pending_fill.order_id);
+            let (Some(destination), Some(rpc_pool)) = (
+                live_config.chain(pending_fill.destination_chain_id as u64),
+                live_config
+                    .rpc_pools
+                    .get(&(pending_fill.destination_chain_id as u64)),
+            ) else {
+                continue;
+            };
+            let Some(tx_hash) = &pending_fill.tx_hash else {
+                // The transaction may or may not have been sent, only the wallet can tell
+                warn!(
+                    order_id,
+                    "Fill reserved without a transaction hash, check the filler wallet"
+                );
+                continue;
+            };
+            let tx_hash = TxHash::from_slice(tx_hash);
+
+            let receipt = rpc_pool
+                .call(|provider| async move { provider.get_transaction_receipt(tx_hash).await })
+                .await?;
+            if let Some(receipt) = receipt {
+                info!(order_id, %tx_hash, "Settling pending fill");
+                self.settle_fill(live_config, destination, &pending_fill, &receipt)
+                    .await?;
+                continue;
+            }
+
+            let age = chrono::Utc::now().naive_utc() - pending_fill.created_at;
+            if age.num_seconds() < DROPPED_FILL_TIMEOUT {
+                continue;
+            }
+            let transaction = rpc_pool
+                .call(|provider| async move { provider.get_transaction_by_hash(tx_hash).await })
+                .await?;
+            if transaction.is_none() {
+                warn!(
+                    order_id,
+                    %tx_hash,
+                    "Fill transaction dropped, releasing its reservation"
+                );
+                self.spending_counter_repository
+                    .release_fill(&pending_fill)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Settler of the route towards `destination`. When its `SettlerUpdated` event was not
+    /// indexed, e.g. it predates `start_block`, it is read from the orderbook and stored as of
+    /// the block it was read at, so a later event still takes precedence.
+    async fn settler_address(
+        &self,
+        live_config: &LiveConfig,
+        destination: &ChainConfig,
+    ) -> Result<Address> {
+        let chain_id = self.chain_config.chain_id;
+        if let Some(settler) = self
+            .contract_config_repository
+            .get_settler(chain_id, destination.chain_id)
+            .await?
+        {
+            return Ok(settler);
+        }
+
+        let rpc_pool = live_config.rpc_pools.get(&chain_id).ok_or(eyre::eyre!(
+            "No RPC pool for chain {}",
+            self.chain_config.name
+        ))?;
+        let block_number = rpc_pool
+            .call(|provider| async move { provider.get_block_number().await })
+            .await?;
+        let tx = TransactionRequest::default()
+            .to(self.chain_config.order_contract_address.parse()?)
+            .input(
+                Orderbook::settlersCall {
+                    chain: U256::from(destination.chain_id),
+                }
+                .abi_encode()
+                .into(),
+            );
+        let output = rpc_pool
+            .call(|provider| {
+                let tx = tx.clone();
+                async move {
+                    provider
+                        .call(&tx)
+                        .block(BlockId::number(block_number))
+                        .await
+                }
+            })
+            .await?;
+        let settler = Orderbook::settlersCall::abi_decode_returns(&output, true)?.settler;
+
+        info!(
+            chain_name = destination.name,
+            %settler,
+            block_number,
+            "Settler read from the orderbook"
+        );
+        self.contract_config_repository
+            .set_config(
+                chain_id,
+                &settler_key(destination.chain_id),
+                settler.to_vec(),
+                block_number,
+            )
+            .await?;
+        Ok(settler)
+    }
+
+    /// Fills an order created on this chain by calling the settler on its destination chain.
+    async fn fill_order(
+        &self,
+        live_config: &LiveConfig,
+        order: &order::Model,
+        valid_order: ValidOrder<'_>,
+    ) -> Result<()> {
+        let order_id = hex::encode(&order.order_id);
+        let ValidOrder {
+            source,
+            destination,
+            order: solidity_order,
+            hook,
+        } = valid_order;
+        Span::current().record("destination", &destination.name);
+        let signer: PrivateKeySigner = destination
+            .filler_private_key
+            .as_ref()
+            .ok_or(eyre::eyre!(
+                "No filler private key configured for chain {}",
+                destination.name
+            ))?
+            .expose()
+            .parse()?;
+        let prices = TokenPrices::fetch(live_config, source, destination, &solidity_order).await?;
+        let notional = tokens_notional(destination, &solidity_order.outputs, &prices)?;
+
+        // Settlers are set on this chain's orderbook, an unset one means the route is closed
+        let settler_address = self.settler_address(live_config, destination).await?;
+        if settler_address.is_zero() {
+            warn!(
+                order_id,
+                chain_name = destination.name,
+                "No settler registered for the destination chain, not filling order"
+            );
+            self.count_fill_failure("no_settler");
+            return Ok(());
+        }
+
+        let rpc_pool = live_config
+            .rpc_pools
+            .get(&destination.chain_id)
+            .ok_or(eyre::eyre!("No RPC pool for chain {}", destination.name))?;
+        let filler_address = signer.address();
+        let multicall_address = destination
+            .multicall_address
+            .as_deref()
+            .map(str::parse)
+            .transpose()?;
+
+        // The order stays ready, so it is filled once the wallet is topped up
+        let shortfalls = inventory_shortfalls(
+            rpc_pool,
+            multicall_address,
+            filler_address,
+            &solidity_order.outputs,
+        )
+        .await?;
+        if !shortfalls.is_empty() {
+            let shortfalls = shortfalls
+                .iter()
+                .map(|shortfall| {
+                    let token = address_to_bytes64_vec(shortfall.token);
+                    format!(
+                        "{} held, {} needed",
+                        live_config.token_registry.format_amount(
+                            destination.chain_id,
+                            &token,
+                            shortfall.balance
+                        ),
+                        live_config.token_registry.format_amount(
+                            destination.chain_id,
+                            &token,
+                            shortfall.required
+                        ),
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            warn!(
+                order_id,
+                %filler_address,
+                shortfalls,
+                "Insufficient inventory, not filling order"
+            );
+            alert::raise(Alert::new(
+                Severity::Warning,
+                "insufficient_inventory",
+                &destination.name,
+                format!("Not filling order {order_id}, wallet {filler_address}: {shortfalls}"),
+            ));
+            self.count_fill_failure("insufficient_inventory");
+            return Ok(());
+        }
+
+        // Hooks only get the gas the policy allows, zero means the order has none.
+        let max_gas = match &hook {
+            Some(_) => U256::from(destination.hooks.gas_limit()),
+            None => U256::ZERO,
+        };
+
+        // For sponsored orders the filler pays for the settlement message back to the source
+        let messaging_fee = if solidity_order.sponsored {
+            messaging_fee(source, destination)?
+        } else {
+            U256::ZERO
+        };
+
+        let fill = TransactionRequest::default()
+            .from(filler_address)
+            .to(settler_address)
+            .value(messaging_fee)
+            .input(
+                Settler::fillOrderCall {
+                    order: (&solidity_order).try_into()?,
+                    orderId: FixedBytes::from_slice(&order.order_id),
+                    maxGas: max_gas,
+                }
+                .abi_encode()
+                .into(),
+            );
+
+        // The hook runs once the settler delivered the outputs, so the whole fill is simulated
+        if let Some(hook) = &hook {
+            if let HookSimulation::Reverted(reason) = simulate_fill(rpc_pool, &fill).await? {
+                info!(
+                    order_id,
+                    recipient = %hook.recipient,
+                    reason,
+                    "Fill simulation with the hook reverted, rejecting order"
+                );
+                self.order_repository
+                    .reject_order(
+                        order.order_id.clone(),
+                        RejectionReason::HookSimulationFailed.code(),
+                    )
+                    .await?;
+                self.count_fill_failure("hook_reverted");
+                return Ok(());
+            }
+        }
+
+        // Limits are checked against the fee cap, the most the transaction can end up paying
+        let fees = rpc_pool
+            .call(|provider| async move { provider.estimate_eip1559_fees(None).await })
+            .await?;
+        let gas = rpc_pool
+            .call(|provider| {
+                let fill = fill.clone();
+                async move { provider.estimate_gas(&fill).await }
+            })
+            .await?;
+        let messaging_fee = wei_to_native(messaging_fee.try_into()?);
+        let cost = FillCost {
+            max_fee_per_gas: fees.max_fee_per_gas,
+            fee: wei_to_native(gas as u128 * fees.max_fee_per_gas) + messaging_fee,
+            notional,
+        };
+
+        let quote = quote_fill(source, destination, &solidity_order, &prices, cost.fee)?;
+        if !quote.is_profitable(source.profitability_threshold) {
+            info!(
+                order_id,
+                profit = quote.profit(),
+                margin = quote.margin(),
+                "Order is not profitable, not filling it"
+            );
+            self.count_fill_failure("unprofitable");
+            return Ok(());
+        }
+
+        // Transactions are sent through the best endpoint, which also watches for the receipt
+        let rpc_url = rpc_pool.http_url().ok_or(eyre::eyre!(
+            "No RPC endpoint configured for chain {}",
+            destination.name
+        ))?;
+        let provider = ProviderBuilder::new()
+            .with_recommended_fillers()
+            .wallet(EthereumWallet::from(signer))
+            .on_builtin(&rpc_url)
+            .await?;
+
+        let pending_fill = match self
+            .reserve_spending(order, destination, &cost, messaging_fee)
+            .await?
+        {
+            Ok(pending_fill) => pending_fill,
+            Err(breach) => {
+                warn!(
+                    order_id,
+                    chain_name = destination.name,
+                    limit = breach.kind(),
+                    %breach,
+                    "Spending limit reached, not filling order"
+                );
+                alert::raise(Alert::new(
+                    Severity::Warning,
+                    "spending_limit_reached",
+                    &destination.name,
+                    format!("Not filling order {order_id}: {breach}"),
+                ));
+                self.count_fill_failure("spending_limit");
+                return Ok(());
+            }
+        };
+
+        let fill = fill
+            .gas_limit(gas)
+            .max_fee_per_gas(fees.max_fee_per_gas)
+            .max_priority_fee_per_gas(fees.max_priority_fee_per_gas);
+        // Covers the transaction from its submission to its receipt
+        let tx_span = info_span!(
+            "fill_transaction",
+            chain = destination.name,
+            tx_hash = field::Empty,
+        );
+        let pending_tx = match provider
+            .send_transaction(fill)
+            .instrument(tx_span.clone())
+            .await
+        {
+            Ok(pending_tx) => pending_tx,
+            Err(e) => {
+                self.spending_counter_repository
+                    .release_fill(&pending_fill)
+                    .await?;
+                return Err(e.into());
+            }
+        };
+        let tx_hash = *pending_tx.tx_hash();
+        Span::current().record("tx_hash", tx_hash.to_string());
+        tx_span.record("tx_hash", tx_hash.to_string());
+        info!(order_id, %tx_hash, "Fill transaction sent");
+        self.spending_counter_repository
+            .set_fill_tx_hash(order.order_id.clone(), tx_hash.to_vec())
+            .await?;
+        // Without a receipt the fill stays pending, and is settled on a later poll
+        let receipt = pending_tx.get_receipt().instrument(tx_span).await?;
+        let Some(fee) = self
+            .settle_fill(live_config, destination, &pending_fill, &receipt)
+            .await?
+        else {
+            return Ok(());
+        };
+
+        metrics()
+            .profit_realised
+            .with_label_values(&[&self.chain_config.name])
+            .inc_by(quote.profit().max(0.0));
+        info!(
+            order_id,
+            tx_hash = %receipt.transaction_hash,
+            inputs = live_config
+                .token_registry
+                .format_tokens(source.chain_id, &solidity_order.inputs),
+            outputs = live_config
+                .token_registry
+                .format_tokens(destination.chain_id, &solidity_order.outputs),
+            fee,
+            notional,
+            profit = quote.profit(),
+            "Order filled successfully!"
+        );
+        Ok(())
+    }
 }
 
 impl Service for Filler {
     async fn _run(&self) -> Result<()> {
-        loop {
-            tokio::time::sleep(tokio::time::Duration::from_millis(
-                self.chain_config.filler_poll_interval,
-            ))
+        self.update_wallet_balance(&self.live_config(), &self.chain_config)
             .await;
+        loop {
+            // Picks up the configuration reloaded since the last poll
+            let live_config = self.live_config();
+            let poll_interval = live_config
+                .chain(self.chain_config.chain_id)
+                .map_or(self.chain_config.filler_poll_interval, |chain| {
+                    chain.filler_poll_interval
+                });
+            tokio::select! {
+                _ = self.shutdown.cancelled() => return Ok(()),
+                _ = tokio::time::sleep(tokio::time::Duration::from_millis(poll_interval)) => {}
+            }
+            self.settle_pending_fills(&live_config).await?;
+
+            let paused = self
+                .chain_state_repository
+                .get_chain_state(self.chain_config.chain_id)
+                .await?
+                .is_some_and(|state| state.filler_paused);
+            if paused {
+                debug!(chain_name = self.chain_config.name, "Filler paused");
+                self.report_success();
+                continue;
+            }
 
             let ready_orders = self
                 .order_repository
                 .get_ready_orders(self.chain_config.chain_id)
                 .await?;
+            metrics()
+                .ready_orders
+                .with_label_values(&[&self.chain_config.name])
+                .set(ready_orders.len() as i64);
 
             for order in ready_orders {
-                info!(
-                    order_id = hex::encode(order.order_id),
-                    "Trying to fill ready order",
+                if self.shutdown.is_cancelled() {
+                    return Ok(());
+                }
+                let span = info_span!(
+                    "fill_order",
+                    chain = self.chain_config.name,
+                    order_id = hex::encode(&order.order_id),
+                    destination = field::Empty,
+                    tx_hash = field::Empty,
                 );
-                // TODO:
-                // 2. Try to Fill the Orders
-                //   - call fillOrder on the target smart contract's router
-                // 3. If successful, mark as done the order
+                order_root_span(&span, &order.order_id);
+                self.process_ready_order(&live_config, &order)
+                    .instrument(span)
+                    .await;
             }
+            self.report_success();
         }
     }
 
     fn service_name(&self) -> String {
         format!("{} Filler", self.chain_config.name)
     }
+
+    fn chain_config(&self) -> &ChainConfig {
+        &self.chain_config
+    }
+
+    fn shutdown(&self) -> Option<&CancellationToken> {
+        Some(&self.shutdown)
+    }
 }
@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+
+use alloy::{
+    primitives::{Address, I256},
+    providers::Provider,
+    rpc::types::TransactionRequest,
+    sol_types::SolCall,
+};
+use eyre::Result;
+
+use crate::{
+    context::{ChainConfig, TokenConfig},
+    rpc::RpcPool,
+    runtime::LiveConfig,
+    solidity::{bytes64_to_address, bytes64_to_vec, AggregatorV3, Validator},
+};
+
+/// Seconds after which a price feed answer is too old to value a fill.
+const MAX_PRICE_AGE: u64 = 86_400;
+
+/// Price of one whole token in notional units, by chain and token address.
+#[derive(Debug, Default)]
+pub struct TokenPrices(HashMap<(u64, Address), f64>);
+
+impl FromIterator<((u64, Address), f64)> for TokenPrices {
+    fn from_iter<T: IntoIterator<Item = ((u64, Address), f64)>>(prices: T) -> Self {
+        Self(prices.into_iter().collect())
+    }
+}
+
+impl TokenPrices {
+    /// Prices the tokens of an order, each on the chain it is paid on, from the token's
+    /// `price_feed` when it has one and its configured `price` otherwise.
+    pub async fn fetch(
+        live_config: &LiveConfig,
+        source: &ChainConfig,
+        destination: &ChainConfig,
+        order: &Validator::Order,
+    ) -> Result<Self> {
+        let mut prices = HashMap::new();
+        for (chain, tokens) in [(source, &order.inputs), (destination, &order.outputs)] {
+            for token in tokens {
+                let (address, token_config) = token_config(chain, token)?;
+                if prices.contains_key(&(chain.chain_id, address)) {
+                    continue;
+                }
+                let price = match (&token_config.price_feed, token_config.price) {
+                    (Some(price_feed), _) => {
+                        let rpc_pool =
+                            live_config
+                                .rpc_pools
+                                .get(&chain.chain_id)
+                                .ok_or(eyre::eyre!(
+                                    "No RPC endpoint configured for chain {}",
+                                    chain.name
+                                ))?;
+                        read_price_feed(rpc_pool, price_feed.parse()?).await?
+                    }
+                    (None, Some(price)) => price,
+                    (None, None) => {
+                        return Err(eyre::eyre!(
+                            "Token {} has no price_feed or price configured on chain {}",
+                            token_config.symbol,
+                            chain.name
+                        ))
+                    }
+                };
+                prices.insert((chain.chain_id, address), price);
+            }
+        }
+        Ok(Self(prices))
+    }
+}
+
+/// Latest answer of a Chainlink aggregator, scaled by its decimals.
+async fn read_price_feed(rpc_pool: &RpcPool, price_feed: Address) -> Result<f64> {
+    let call = |input: Vec<u8>| {
+        let tx = TransactionRequest::default()
+            .to(price_feed)
+            .input(input.into());
+        rpc_pool.call(move |provider| {
+            let tx = tx.clone();
+            async move { provider.call(&tx).await }
+        })
+    };
+    let decimals = AggregatorV3::decimalsCall::abi_decode_returns(
+        &call(AggregatorV3::decimalsCall {}.abi_encode()).await?,
+        true,
+    )?
+    ._0;
+    let round = AggregatorV3::latestRoundDataCall::abi_decode_returns(
+        &call(AggregatorV3::latestRoundDataCall {}.abi_encode()).await?,
+        true,
+    )?;
+
+    let age = (chrono::Utc::now().timestamp() as u64).saturating_sub(round.updatedAt.to());
+    if round.answer <= I256::ZERO || age > MAX_PRICE_AGE {
+        return Err(eyre::eyre!(
+            "Price feed {price_feed} answered {} {age} seconds ago",
+            round.answer
+        ));
+    }
+    Ok(f64::from(round.answer.into_raw()) / 10f64.powi(decimals.into()))
+}
+
+fn token_config<'a>(
+    chain: &'a ChainConfig,
+    token: &Validator::Token,
+) -> Result<(Address, &'a TokenConfig)> {
+    let token_address = bytes64_to_vec(&token.tokenAddress);
+    bytes64_to_address(&token_address)
+        .and_then(|address| Some((address, chain.find_token(address)?)))
+        .ok_or(eyre::eyre!(
+            "Token 0x{} is not configured on chain {}",
+            hex::encode(&token_address),
+            chain.name
+        ))
+}
+
+/// Values token amounts paid on `chain` in notional units, using the configured token
+/// decimals and the fetched prices.
+pub fn tokens_notional(
+    chain: &ChainConfig,
+    tokens: &[Validator::Token],
+    prices: &TokenPrices,
+) -> Result<f64> {
+    tokens.iter().try_fold(0.0, |notional, token| {
+        let (address, token_config) = token_config(chain, token)?;
+        let decimals = token_config.decimals.ok_or(eyre::eyre!(
+            "Token {} has no decimals configured",
+            token_config.symbol
+        ))?;
+        let price = prices
+            .0
+            .get(&(chain.chain_id, address))
+            .ok_or(eyre::eyre!("Token {} has no price", token_config.symbol))?;
+        Ok(notional + f64::from(token.amount) / 10f64.powi(decimals.into()) * price)
+    })
+}
+
+/// What the filler receives, pays out and spends for a fill, all in notional units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FillQuote {
+    pub input_value: f64,
+    pub output_value: f64,
+    pub cost_value: f64,
+}
+
+impl FillQuote {
+    pub fn profit(&self) -> f64 {
+        self.input_value - self.output_value - self.cost_value
+    }
+
+    pub fn margin(&self) -> f64 {
+        if self.output_value == 0.0 {
+            return 0.0;
+        }
+        self.profit() / self.output_value
+    }
+
+    pub fn is_profitable(&self, profitability_threshold: f32) -> bool {
+        self.profit() > 0.0 && self.margin() >= profitability_threshold.into()
+    }
+}
+
+/// Values a fill. `native_spent` covers gas and, for sponsored orders, the messaging fee,
+/// both paid on the destination chain.
+pub fn quote_fill(
+    source: &ChainConfig,
+    destination: &ChainConfig,
+    order: &Validator::Order,
+    prices: &TokenPrices,
+    native_spent: f64,
+) -> Result<FillQuote> {
+    let cost_value = if native_spent == 0.0 {
+        0.0
+    } else {
+        let native_token_price = destination.native_token_price.ok_or(eyre::eyre!(
+            "No native token price configured for chain {}",
+            destination.name
+        ))?;
+        native_spent * native_token_price
+    };
+
+    Ok(FillQuote {
+        input_value: tokens_notional(source, &order.inputs, prices)?,
+        output_value: tokens_notional(destination, &order.outputs, prices)?,
+        cost_value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{Address, FixedBytes, U256};
+
+    use super::{quote_fill, FillQuote, TokenPrices};
+    use crate::{
+        context::{ChainConfig, TokenConfig},
+        solidity::{_bytes64, Validator},
+    };
+
+    const SOURCE_TOKEN: &str = "0x700b6A60ce7EaaEA56F065753d8dcB9653dbAD35";
+    const DESTINATION_TOKEN: &str = "0xA15BB66138824a1c7167f5E85b957d04Dd34E468";
+
+    fn token(address: &str, amount: u128) -> Validator::Token {
+        Validator::Token {
+            tokenAddress: _bytes64 {
+                lower: address.parse::<Address>().unwrap().into_word(),
+                upper: FixedBytes::ZERO,
+            },
+            tokenId: U256::MAX,
+            amount: U256::from(amount),
+        }
+    }
+
+    fn chain(token: &str, decimals: u8) -> ChainConfig {
+        ChainConfig {
+            name: "chain".to_string(),
+            native_token_price: Some(2_000.0),
+            tokens: vec![TokenConfig {
+                symbol: "USDC".to_string(),
+                token_type: "ERC20".to_string(),
+                address: token.to_string(),
+                decimals: Some(decimals),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn prices(source_price: f64, destination_price: f64) -> TokenPrices {
+        [
+            ((0, SOURCE_TOKEN.parse().unwrap()), source_price),
+            ((0, DESTINATION_TOKEN.parse().unwrap()), destination_price),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    fn order() -> Validator::Order {
+        let empty = _bytes64 {
+            lower: FixedBytes::ZERO,
+            upper: FixedBytes::ZERO,
+        };
+        Validator::Order {
+            user: empty.clone(),
+            filler: empty.clone(),
+            inputs: vec![token(SOURCE_TOKEN, 1_010_000_000)],
+            outputs: vec![token(DESTINATION_TOKEN, 1_000_000_000_000_000_000_000)],
+            sourceChainSelector: U256::from(1),
+            destinationChainSelector: U256::from(2),
+            sponsored: false,
+            primaryFillerDeadline: U256::ZERO,
+            deadline: U256::ZERO,
+            callRecipient: empty,
+            callData: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_quote_fill() {
+        let source = chain(SOURCE_TOKEN, 6);
+        let destination = chain(DESTINATION_TOKEN, 18);
+
+        let quote = quote_fill(&source, &destination, &order(), &prices(1.0, 1.0), 0.0).unwrap();
+        assert_eq!(
+            quote,
+            FillQuote {
+                input_value: 1_010.0,
+                output_value: 1_000.0,
+                cost_value: 0.0,
+            }
+        );
+        assert!(quote.is_profitable(0.01));
+        assert!(!quote.is_profitable(0.02));
+
+        // 0.002 native for gas and messaging at 2000 per native token
+        let quote = quote_fill(&source, &destination, &order(), &prices(1.0, 1.0), 0.002).unwrap();
+        assert_eq!(quote.cost_value, 4.0);
+        assert_eq!(quote.profit(), 6.0);
+        assert!(!quote.is_profitable(0.01));
+    }
+
+    #[test]
+    fn test_quote_fill_without_native_price() {
+        let source = chain(SOURCE_TOKEN, 6);
+        let mut destination = chain(DESTINATION_TOKEN, 18);
+        destination.native_token_price = None;
+
+        assert!(quote_fill(&source, &destination, &order(), &prices(1.0, 1.0), 0.0).is_ok());
+        assert!(quote_fill(&source, &destination, &order(), &prices(1.0, 1.0), 0.001).is_err());
+    }
+
+    #[test]
+    fn test_quote_fill_mixed_assets() {
+        let source = chain(SOURCE_TOKEN, 6);
+        let destination = chain(DESTINATION_TOKEN, 18);
+
+        // 1010 input tokens worth 0.5 each for 1000 output tokens worth 1 each
+        let quote = quote_fill(&source, &destination, &order(), &prices(0.5, 1.0), 0.0).unwrap();
+        assert_eq!(quote.input_value, 505.0);
+        assert_eq!(quote.output_value, 1_000.0);
+        assert!(!quote.is_profitable(0.0));
+
+        let unpriced = quote_fill(
+            &source,
+            &destination,
+            &order(),
+            &TokenPrices::default(),
+            0.0,
+        );
+        assert!(unpriced.is_err());
+    }
+}
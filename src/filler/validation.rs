@@ -0,0 +1,250 @@
+use std::fmt;
+
+use alloy::primitives::{Address, U256};
+
+use super::hooks::{check_hook_policy, decode_hook, Hook};
+use crate::{
+    context::ChainConfig,
+    solidity::{bytes64_to_address, bytes64_to_vec, Validator},
+};
+
+const ERC20: &str = "ERC20";
+/// Token id the orderbook gives the ERC20 legs of an order.
+const ERC20_TOKEN_ID: U256 = U256::MAX;
+
+/// Why an order was turned down, persisted on the order as its reason code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RejectionReason {
+    UnsupportedDestination,
+    UnconfiguredToken,
+    UnsupportedTokenType,
+    HooksDisabled,
+    InvalidHook,
+    HookRecipientNotAllowed,
+    HookSelectorNotAllowed,
+    HookSimulationFailed,
+    DeniedUser,
+    DeadlineTooShort,
+    /// Set by an operator through the admin API.
+    Blacklisted,
+}
+
+impl RejectionReason {
+    pub fn code(&self) -> &'static str {
+        match self {
+            RejectionReason::UnsupportedDestination => "unsupported_destination",
+            RejectionReason::UnconfiguredToken => "unconfigured_token",
+            RejectionReason::UnsupportedTokenType => "unsupported_token_type",
+            RejectionReason::HooksDisabled => "hooks_disabled",
+            RejectionReason::InvalidHook => "invalid_hook",
+            RejectionReason::HookRecipientNotAllowed => "hook_recipient_not_allowed",
+            RejectionReason::HookSelectorNotAllowed => "hook_selector_not_allowed",
+            RejectionReason::HookSimulationFailed => "hook_simulation_failed",
+            RejectionReason::DeniedUser => "denied_user",
+            RejectionReason::DeadlineTooShort => "deadline_too_short",
+            RejectionReason::Blacklisted => "blacklisted",
+        }
+    }
+}
+
+impl fmt::Display for RejectionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Checks an order created on `source` against the rules of both ends of the route,
+/// returning the destination hook it carries, if any.
+pub fn validate_order(
+    source: &ChainConfig,
+    destination: &ChainConfig,
+    order: &Validator::Order,
+    now: i64,
+) -> Result<Option<Hook>, RejectionReason> {
+    let user = bytes64_to_address(&bytes64_to_vec(&order.user));
+    let denied = source.validation.denied_users.iter().any(|denied_user| {
+        denied_user
+            .parse::<Address>()
+            .is_ok_and(|denied_user| Some(denied_user) == user)
+    });
+    if denied {
+        return Err(RejectionReason::DeniedUser);
+    }
+
+    if let Some(min_deadline_window) = source.validation.min_deadline_window {
+        let deadline = order.deadline.saturating_to::<i64>();
+        if deadline.saturating_sub(now) < min_deadline_window as i64 {
+            return Err(RejectionReason::DeadlineTooShort);
+        }
+    }
+
+    check_tokens(source, &order.inputs)?;
+    check_tokens(destination, &order.outputs)?;
+
+    let hook = decode_hook(order)?;
+    if let Some(hook) = &hook {
+        check_hook_policy(&destination.hooks, hook)?;
+    }
+
+    Ok(hook)
+}
+
+fn check_tokens(chain: &ChainConfig, tokens: &[Validator::Token]) -> Result<(), RejectionReason> {
+    for token in tokens {
+        let token_config = bytes64_to_address(&bytes64_to_vec(&token.tokenAddress))
+            .and_then(|address| chain.find_token(address))
+            .ok_or(RejectionReason::UnconfiguredToken)?;
+        if !token_config.token_type.eq_ignore_ascii_case(ERC20) || token.tokenId != ERC20_TOKEN_ID {
+            return Err(RejectionReason::UnsupportedTokenType);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{Address, FixedBytes, U256};
+
+    use super::{validate_order, RejectionReason};
+    use crate::{
+        context::{ChainConfig, TokenConfig},
+        solidity::{_bytes64, order_created_fixture, Validator},
+    };
+
+    const USER: &str = "0xa0Ee7A142d267C1f36714E4a8F75612F20a79720";
+    const SOURCE_TOKEN: &str = "0x700b6A60ce7EaaEA56F065753d8dcB9653dbAD35";
+    const DESTINATION_TOKEN: &str = "0xA15BB66138824a1c7167f5E85b957d04Dd34E468";
+    const NOW: i64 = 1_735_566_882;
+
+    fn bytes64(address: &str) -> _bytes64 {
+        let address: Address = address.parse().unwrap();
+        _bytes64 {
+            lower: address.into_word(),
+            upper: FixedBytes::ZERO,
+        }
+    }
+
+    fn chain(chain_id: u64, token: &str) -> ChainConfig {
+        ChainConfig {
+            name: format!("chain-{chain_id}"),
+            chain_id,
+            tokens: vec![TokenConfig {
+                symbol: "USDC".to_string(),
+                token_type: "ERC20".to_string(),
+                address: token.to_string(),
+                decimals: Some(6),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn order() -> Validator::Order {
+        Validator::Order {
+            user: bytes64(USER),
+            filler: bytes64(USER),
+            inputs: vec![Validator::Token {
+                tokenAddress: bytes64(SOURCE_TOKEN),
+                tokenId: U256::MAX,
+                amount: U256::from(1_000_000),
+            }],
+            outputs: vec![Validator::Token {
+                tokenAddress: bytes64(DESTINATION_TOKEN),
+                tokenId: U256::MAX,
+                amount: U256::from(990_000),
+            }],
+            sourceChainSelector: U256::from(1),
+            destinationChainSelector: U256::from(2),
+            sponsored: false,
+            primaryFillerDeadline: U256::from(NOW + 600),
+            deadline: U256::from(NOW + 3_600),
+            callRecipient: _bytes64 {
+                lower: FixedBytes::ZERO,
+                upper: FixedBytes::ZERO,
+            },
+            callData: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_valid_order() {
+        let source = chain(1, SOURCE_TOKEN);
+        let destination = chain(2, DESTINATION_TOKEN);
+
+        assert_eq!(
+            validate_order(&source, &destination, &order(), NOW),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn test_order_created_is_valid() {
+        let order = order_created_fixture().order;
+        let source = chain(31337, SOURCE_TOKEN);
+        let destination = chain(31337, DESTINATION_TOKEN);
+
+        assert_eq!(validate_order(&source, &destination, &order, NOW), Ok(None));
+    }
+
+    #[test]
+    fn test_token_rejections() {
+        let source = chain(1, SOURCE_TOKEN);
+        let destination = chain(2, DESTINATION_TOKEN);
+
+        let mut unconfigured = order();
+        unconfigured.outputs[0].tokenAddress = bytes64(SOURCE_TOKEN);
+        assert_eq!(
+            validate_order(&source, &destination, &unconfigured, NOW),
+            Err(RejectionReason::UnconfiguredToken)
+        );
+
+        for token_id in [U256::ZERO, U256::from(7)] {
+            let mut nft = order();
+            nft.inputs[0].tokenId = token_id;
+            assert_eq!(
+                validate_order(&source, &destination, &nft, NOW),
+                Err(RejectionReason::UnsupportedTokenType)
+            );
+        }
+
+        let mut erc1155 = chain(2, DESTINATION_TOKEN);
+        erc1155.tokens[0].token_type = "ERC1155".to_string();
+        assert_eq!(
+            validate_order(&source, &erc1155, &order(), NOW),
+            Err(RejectionReason::UnsupportedTokenType)
+        );
+    }
+
+    #[test]
+    fn test_order_rejections() {
+        let mut source = chain(1, SOURCE_TOKEN);
+        let mut destination = chain(2, DESTINATION_TOKEN);
+
+        let mut hook = order();
+        hook.callRecipient = bytes64(USER);
+        hook.callData = vec![0xde, 0xad, 0xbe, 0xef].into();
+        assert_eq!(
+            validate_order(&source, &destination, &hook, NOW),
+            Err(RejectionReason::HooksDisabled)
+        );
+        destination.hooks.enabled = true;
+        destination.hooks.allowed_recipients = vec![USER.to_string()];
+        destination.hooks.allowed_selectors = vec!["0xdeadbeef".to_string()];
+        assert!(validate_order(&source, &destination, &hook, NOW)
+            .unwrap()
+            .is_some());
+
+        source.validation.min_deadline_window = Some(7_200);
+        assert_eq!(
+            validate_order(&source, &destination, &order(), NOW),
+            Err(RejectionReason::DeadlineTooShort)
+        );
+
+        source.validation.min_deadline_window = None;
+        source.validation.denied_users = vec![USER.to_lowercase()];
+        assert_eq!(
+            validate_order(&source, &destination, &order(), NOW),
+            Err(RejectionReason::DeniedUser)
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use std::{
+    collections::BTreeMap,
+    sync::{LazyLock, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::context::HealthConfig;
+
+pub(crate) const DEFAULT_MAX_LAG_BLOCKS: u64 = 50;
+const DEFAULT_MAX_HEAD_AGE: u64 = 120;
+const DEFAULT_MAX_CONSECUTIVE_ERRORS: u32 = 5;
+
+static HEALTH: LazyLock<Health> = LazyLock::new(Health::default);
+
+/// What the services last reported about themselves, keyed by service name.
+#[derive(Default)]
+pub struct Health {
+    services: Mutex<BTreeMap<String, ServiceHealth>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ServiceHealth {
+    pub chain: String,
+    pub last_success: Option<DateTime<Utc>>,
+    pub consecutive_errors: u32,
+    pub last_error: Option<String>,
+    pub indexed_head: Option<u64>,
+    pub chain_head: Option<u64>,
+    /// When the chain head was last read from the RPC.
+    pub head_updated_at: Option<DateTime<Utc>>,
+    #[serde(skip)]
+    config: HealthConfig,
+}
+
+impl ServiceHealth {
+    /// Too many errors in a row means the service is stuck in its retry loop.
+    pub fn is_live(&self) -> bool {
+        self.consecutive_errors
+            < self
+                .config
+                .max_consecutive_errors
+                .unwrap_or(DEFAULT_MAX_CONSECUTIVE_ERRORS)
+    }
+
+    /// Reasons for the service not to be ready, empty when it is.
+    pub fn unready_reasons(&self, now: DateTime<Utc>) -> Vec<String> {
+        let mut reasons = vec![];
+        if self.last_success.is_none() {
+            reasons.push("no successful loop yet".to_string());
+        }
+        if self.consecutive_errors > 0 {
+            reasons.push(format!("{} consecutive errors", self.consecutive_errors));
+        }
+
+        let max_lag_blocks = self.config.max_lag_blocks.unwrap_or(DEFAULT_MAX_LAG_BLOCKS);
+        if let Some(lag) = self.lag_blocks() {
+            if lag > max_lag_blocks {
+                reasons.push(format!("{lag} blocks behind the chain head"));
+            }
+        }
+
+        let max_head_age = self.config.max_head_age.unwrap_or(DEFAULT_MAX_HEAD_AGE) as i64;
+        if let Some(head_updated_at) = self.head_updated_at {
+            let head_age = (now - head_updated_at).num_seconds();
+            if head_age > max_head_age {
+                reasons.push(format!("chain head not updated for {head_age}s"));
+            }
+        }
+        reasons
+    }
+
+    pub fn lag_blocks(&self) -> Option<u64> {
+        Some(self.chain_head?.saturating_sub(self.indexed_head?))
+    }
+}
+
+impl Health {
+    pub fn register(&self, service: &str, chain: &str, config: &HealthConfig) {
+        self.services
+            .lock()
+            .unwrap()
+            .entry(service.to_string())
+            .or_insert_with(|| ServiceHealth {
+                chain: chain.to_string(),
+                last_success: None,
+                consecutive_errors: 0,
+                last_error: None,
+                indexed_head: None,
+                chain_head: None,
+                head_updated_at: None,
+                config: config.clone(),
+            });
+    }
+
+    fn update(&self, service: &str, update: impl FnOnce(&mut ServiceHealth)) {
+        if let Some(service_health) = self.services.lock().unwrap().get_mut(service) {
+            update(service_health);
+        }
+    }
+
+    /// Forgets the services of a chain that was removed from the configuration.
+    pub fn unregister_chain(&self, chain: &str) {
+        self.services
+            .lock()
+            .unwrap()
+            .retain(|_, service| service.chain != chain);
+    }
+
+    /// Applies the thresholds of a reloaded configuration to the running services of a chain.
+    pub fn update_chain_config(&self, chain: &str, config: &HealthConfig) {
+        for service_health in self.services.lock().unwrap().values_mut() {
+            if service_health.chain == chain {
+                service_health.config = config.clone();
+            }
+        }
+    }
+
+    pub fn record_success(&self, service: &str) {
+        self.update(service, |service_health| {
+            service_health.last_success = Some(Utc::now());
+            service_health.consecutive_errors = 0;
+        });
+    }
+
+    pub fn record_error(&self, service: &str, error: String) {
+        self.update(service, |service_health| {
+            service_health.consecutive_errors += 1;
+            service_health.last_error = Some(error);
+        });
+    }
+
+    pub fn record_heads(&self, service: &str, indexed_head: u64, chain_head: u64) {
+        self.update(service, |service_health| {
+            service_health.indexed_head = Some(indexed_head);
+            service_health.chain_head = Some(chain_head);
+            service_health.head_updated_at = Some(Utc::now());
+        });
+    }
+
+    pub fn services(&self) -> BTreeMap<String, ServiceHealth> {
+        self.services.lock().unwrap().clone()
+    }
+}
+
+pub fn health() -> &'static Health {
+    &HEALTH
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::Health;
+    use crate::context::HealthConfig;
+
+    #[test]
+    fn test_readiness() {
+        let health = Health::default();
+        let config = HealthConfig {
+            max_lag_blocks: Some(10),
+            max_head_age: Some(60),
+            max_consecutive_errors: Some(2),
+        };
+        health.register("test Listener", "test", &config);
+        let service = |health: &Health| health.services()["test Listener"].clone();
+
+        let now = Utc::now();
+        assert_eq!(service(&health).unready_reasons(now).len(), 1);
+
+        health.record_success("test Listener");
+        health.record_heads("test Listener", 95, 100);
+        assert!(service(&health).unready_reasons(now).is_empty());
+
+        health.record_heads("test Listener", 80, 100);
+        health.record_error("test Listener", "RPC down".to_string());
+        let reasons = service(&health).unready_reasons(Utc::now() + Duration::seconds(120));
+        assert_eq!(reasons.len(), 3);
+        assert_eq!(reasons[0], "1 consecutive errors");
+        assert_eq!(reasons[1], "20 blocks behind the chain head");
+        assert!(reasons[2].starts_with("chain head not updated for"));
+        assert!(service(&health).is_live());
+
+        health.record_error("test Listener", "RPC down".to_string());
+        assert!(!service(&health).is_live());
+        assert_eq!(service(&health).last_error.as_deref(), Some("RPC down"));
+
+        health.record_success("test Listener");
+        assert!(service(&health).is_live());
+
+        health.update_chain_config(
+            "test",
+            &HealthConfig {
+                max_lag_blocks: Some(30),
+                ..config
+            },
+        );
+        assert!(service(&health).unready_reasons(now).is_empty());
+    }
+}
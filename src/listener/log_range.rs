@@ -0,0 +1,317 @@
+use std::future::Future;
+
+use alloy::{
+    rpc::types::{Filter, Log},
+    transports::TransportError,
+};
+use eyre::Result;
+use tracing::warn;
+
+/// Successful requests in a row before the range is grown again.
+const GROW_AFTER_SUCCESSES: u32 = 3;
+
+/// Messages providers answer with when a `eth_getLogs` range is too wide or returns too much.
+/// Some providers share the error code with their rate limits, so only the message is
+/// matched, and phrases like "limit exceeded" that rate limit errors use are left out.
+const RANGE_ERRORS: [&str; 10] = [
+    "query returned more than",
+    "too many results",
+    "log response size exceeded",
+    "response size should not",
+    "range too large",
+    "range is too large",
+    "block range is too wide",
+    "exceed maximum block range",
+    "is limited to a",
+    "query timeout exceeded",
+];
+
+/// Number of blocks requested per `eth_getLogs` call, shrunk when the provider refuses a
+/// range and grown back after successes, up to the configured batch size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct LogRange {
+    size: u64,
+    max: u64,
+    successes: u32,
+}
+
+impl LogRange {
+    pub fn new(size: u64, max: u64) -> Self {
+        let max = max.max(1);
+        Self {
+            size: size.clamp(1, max),
+            max,
+            successes: 0,
+        }
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Last block of the range starting at `from_block`, without going past `latest_block`.
+    pub fn end_block(&self, from_block: u64, latest_block: u64) -> u64 {
+        (from_block + self.size - 1).min(latest_block)
+    }
+
+    /// Halves the range, returns `false` when it cannot get any smaller.
+    fn shrink(&mut self) -> bool {
+        self.successes = 0;
+        if self.size == 1 {
+            return false;
+        }
+        self.size /= 2;
+        true
+    }
+
+    fn record_success(&mut self) {
+        self.successes += 1;
+        if self.successes >= GROW_AFTER_SUCCESSES {
+            self.successes = 0;
+            self.size = (self.size * 2).min(self.max);
+        }
+    }
+}
+
+pub(super) fn is_range_error(error: &TransportError) -> bool {
+    error.as_error_resp().is_some_and(|payload| {
+        let message = payload.message.to_lowercase();
+        RANGE_ERRORS
+            .iter()
+            .any(|range_error| message.contains(range_error))
+    })
+}
+
+/// Fetches the logs of the next range starting at `from_block`, shrinking the range until
+/// the provider accepts it. Returns the logs and the last block they cover.
+pub(super) async fn get_logs_adaptive<F, Fut>(
+    get_logs: F,
+    filter: &Filter,
+    from_block: u64,
+    latest_block: u64,
+    log_range: &mut LogRange,
+) -> Result<(Vec<Log>, u64)>
+where
+    F: Fn(Filter) -> Fut,
+    Fut: Future<Output = Result<Vec<Log>, TransportError>>,
+{
+    loop {
+        let to_block = log_range.end_block(from_block, latest_block);
+        let range_filter = filter.clone().from_block(from_block).to_block(to_block);
+        match get_logs(range_filter).await {
+            Ok(logs) => {
+                log_range.record_success();
+                return Ok((logs, to_block));
+            }
+            Err(e) if is_range_error(&e) => {
+                let size = log_range.size();
+                if !log_range.shrink() {
+                    return Err(e.into());
+                }
+                warn!(
+                    from_block,
+                    to_block,
+                    size,
+                    new_size = log_range.size(),
+                    error = %e,
+                    "Log range refused by the provider, shrinking it"
+                );
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        task::{Context, Poll},
+    };
+
+    use alloy::{
+        primitives::Address,
+        providers::{Provider, ProviderBuilder},
+        rpc::{
+            client::RpcClient,
+            json_rpc::{ErrorPayload, RequestPacket, Response, ResponsePacket, ResponsePayload},
+            types::Filter,
+        },
+        transports::{BoxTransport, TransportError, TransportFut},
+    };
+    use serde_json::value::RawValue;
+
+    use super::{get_logs_adaptive, is_range_error, LogRange};
+
+    /// Answers `eth_getLogs` with no logs, or with `error` when the range is wider than
+    /// `max_range`, keeping track of the requested ranges.
+    #[derive(Clone)]
+    struct MockTransport {
+        max_range: u64,
+        error: &'static str,
+        requests: Arc<Mutex<Vec<(u64, u64)>>>,
+    }
+
+    impl tower::Service<RequestPacket> for MockTransport {
+        type Response = ResponsePacket;
+        type Error = TransportError;
+        type Future = TransportFut<'static>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: RequestPacket) -> Self::Future {
+            let RequestPacket::Single(request) = request else {
+                unreachable!("batch requests are not used")
+            };
+            let params: serde_json::Value =
+                serde_json::from_str(request.params().unwrap().get()).unwrap();
+            let block = |key: &str| {
+                u64::from_str_radix(
+                    params[0][key].as_str().unwrap().trim_start_matches("0x"),
+                    16,
+                )
+                .unwrap()
+            };
+            let (from_block, to_block) = (block("fromBlock"), block("toBlock"));
+            self.requests.lock().unwrap().push((from_block, to_block));
+
+            let payload = if to_block - from_block + 1 > self.max_range {
+                ResponsePayload::Failure(ErrorPayload {
+                    code: -32005,
+                    message: self.error.into(),
+                    data: None,
+                })
+            } else {
+                ResponsePayload::Success(RawValue::from_string("[]".to_string()).unwrap())
+            };
+            let response = ResponsePacket::Single(Response {
+                id: request.id().clone(),
+                payload,
+            });
+            Box::pin(async move { Ok(response) })
+        }
+    }
+
+    fn provider(mock: MockTransport) -> impl Provider {
+        ProviderBuilder::new().on_client(RpcClient::new(BoxTransport::new(mock), true))
+    }
+
+    #[test]
+    fn test_log_range() {
+        let mut log_range = LogRange::new(5_000, 1_000);
+        assert_eq!(log_range.size(), 1_000);
+        assert_eq!(log_range.end_block(100, 10_000), 1_099);
+        assert_eq!(log_range.end_block(100, 500), 500);
+
+        assert!(log_range.shrink());
+        assert_eq!(log_range.size(), 500);
+        for _ in 0..3 {
+            log_range.record_success();
+        }
+        assert_eq!(log_range.size(), 1_000);
+        for _ in 0..3 {
+            log_range.record_success();
+        }
+        assert_eq!(log_range.size(), 1_000);
+
+        let mut log_range = LogRange::new(1, 1_000);
+        assert!(!log_range.shrink());
+    }
+
+    #[test]
+    fn test_is_range_error() {
+        let error = |message: &str| {
+            TransportError::ErrorResp(ErrorPayload {
+                code: -32005,
+                message: message.to_string().into(),
+                data: None,
+            })
+        };
+        for message in [
+            "query returned more than 10000 results",
+            "Log response size exceeded. You can make eth_getLogs requests with up to a 2K block range",
+            "block range is too wide",
+            "eth_getLogs is limited to a 10,000 range",
+        ] {
+            assert!(is_range_error(&error(message)), "{message}");
+        }
+        for message in [
+            "daily request count exceeded, request rate limited",
+            "project ID request rate exceeded",
+            "rate limit exceeded",
+            "compute units per second limit exceeded for this block range",
+            "execution reverted",
+        ] {
+            assert!(!is_range_error(&error(message)), "{message}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shrink_on_range_errors() -> eyre::Result<()> {
+        for error in [
+            "query returned more than 10000 results",
+            "Log response size exceeded. You can make eth_getLogs requests with up to a 2K block range",
+        ] {
+            let mock = MockTransport {
+                max_range: 300,
+                error,
+                requests: Default::default(),
+            };
+            let requests = mock.requests.clone();
+            let provider = provider(mock);
+            let filter = Filter::new().address(Address::ZERO);
+            let mut log_range = LogRange::new(1_000, 1_000);
+
+            let (logs, to_block) = get_logs_adaptive(
+                |filter| {
+                    let provider = &provider;
+                    async move { provider.get_logs(&filter).await }
+                },
+                &filter,
+                1,
+                10_000,
+                &mut log_range,
+            )
+            .await?;
+
+            assert!(logs.is_empty());
+            assert_eq!(to_block, 250);
+            assert_eq!(log_range.size(), 250);
+            assert_eq!(
+                *requests.lock().unwrap(),
+                vec![(1, 1_000), (1, 500), (1, 250)]
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_other_errors_are_returned() {
+        let mock = MockTransport {
+            max_range: 0,
+            error: "execution reverted",
+            requests: Default::default(),
+        };
+        let requests = mock.requests.clone();
+        let provider = provider(mock);
+        let mut log_range = LogRange::new(1_000, 1_000);
+
+        let result = get_logs_adaptive(
+            |filter| {
+                let provider = &provider;
+                async move { provider.get_logs(&filter).await }
+            },
+            &Filter::new(),
+            1,
+            10_000,
+            &mut log_range,
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(requests.lock().unwrap().len(), 1);
+        assert_eq!(log_range.size(), 1_000);
+    }
+}
@@ -1,173 +1,671 @@
 mod log;
+mod log_range;
+mod settlement;
 
-use std::sync::Arc;
+pub(crate) use log::OrderbookEvent;
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
 
 use crate::{
-    context::ChainConfig,
-    repository::{block_checkpoint::BlockCheckpointRepository, order::OrderRepository},
+    alert::{self, Alert, Severity},
+    context::{redact_url, ChainConfig, CheckpointConfig},
+    health::{health, DEFAULT_MAX_LAG_BLOCKS},
+    metrics::metrics,
+    repository::{
+        block_checkpoint::BlockCheckpointRepository, chain_state::ChainStateRepository,
+        contract_config::ContractConfigRepository, order::OrderRepository,
+        order_event::OrderEventRepository, order_settlement::OrderSettlementRepository,
+    },
+    rpc::RpcPool,
+    runtime::{LiveConfig, LiveConfigReceiver},
     service::Service,
     solidity::Orderbook::{self},
 };
 use alloy::{
-    eips::BlockNumberOrTag,
     primitives::Address,
     providers::{Provider, ProviderBuilder},
-    rpc::types::{BlockTransactionsKind, Filter},
+    rpc::types::{Filter, Log},
     sol_types::SolEvent,
 };
 use eyre::Result;
+use log_range::{get_logs_adaptive, LogRange};
+use settlement::TraceCache;
+use tokio::sync::mpsc;
 use tracing::{debug, info, warn};
 
+const DEFAULT_LISTENER_POLL_INTERVAL: u64 = 5_000;
+const DEFAULT_CONFIRMATIONS: u64 = 2;
+
+const INDEXED_EVENTS: [&str; 5] = [
+    Orderbook::OrderCreated::SIGNATURE,
+    Orderbook::OrderWithdrawn::SIGNATURE,
+    Orderbook::OrderFilled::SIGNATURE,
+    Orderbook::SettlerUpdated::SIGNATURE,
+    Orderbook::OwnershipTransferred::SIGNATURE,
+];
+
 pub(crate) struct Listener {
     chain_config: ChainConfig,
+    rpc_pool: Arc<RpcPool>,
     order_repository: Arc<OrderRepository>,
+    order_event_repository: Arc<OrderEventRepository>,
     block_checkpoint_repository: Arc<BlockCheckpointRepository>,
+    chain_state_repository: Arc<ChainStateRepository>,
+    contract_config_repository: Arc<ContractConfigRepository>,
+    order_settlement_repository: Arc<OrderSettlementRepository>,
+    live_config: LiveConfigReceiver,
+    /// Whether the listener is behind the chain head, so the alert is raised once per episode.
+    lagging: AtomicBool,
+    settlement_traces: Mutex<TraceCache>,
 }
 
 impl Listener {
-    pub async fn new(postgres_url: String, chain_config: ChainConfig) -> Result<Self> {
+    pub async fn new(
+        postgres_url: String,
+        chain_config: ChainConfig,
+        rpc_pool: Arc<RpcPool>,
+        live_config: LiveConfigReceiver,
+    ) -> Result<Self> {
         let order_repository = Arc::new(OrderRepository::new(postgres_url.clone()).await?);
+        let order_event_repository =
+            Arc::new(OrderEventRepository::new(postgres_url.clone()).await?);
         let block_checkpoint_repository =
             Arc::new(BlockCheckpointRepository::new(postgres_url.clone()).await?);
+        let chain_state_repository =
+            Arc::new(ChainStateRepository::new(postgres_url.clone()).await?);
+        let contract_config_repository =
+            Arc::new(ContractConfigRepository::new(postgres_url.clone()).await?);
+        let order_settlement_repository =
+            Arc::new(OrderSettlementRepository::new(postgres_url.clone()).await?);
         Ok(Self {
             chain_config,
+            rpc_pool,
             order_repository,
+            order_event_repository,
             block_checkpoint_repository,
+            chain_state_repository,
+            contract_config_repository,
+            order_settlement_repository,
+            live_config,
+            lagging: AtomicBool::new(false),
+            settlement_traces: Mutex::new(TraceCache::default()),
         })
     }
 
-    async fn run_polling(&self) -> Result<()> {
-        let url = &self.chain_config.rpc_url;
-        let address: Address = self.chain_config.order_contract_address.parse()?;
-        let block_batch_size = self.chain_config.block_batch_size.unwrap_or(1_000);
+    fn live_config(&self) -> Arc<LiveConfig> {
+        self.live_config.borrow().clone()
+    }
+
+    /// Latest configuration of the chain, for the fields that are reloaded while running.
+    fn current_chain_config(&self) -> ChainConfig {
+        self.live_config()
+            .chain(self.chain_config.chain_id)
+            .cloned()
+            .unwrap_or_else(|| self.chain_config.clone())
+    }
+
+    /// Blocks behind the chain head whose logs are not final yet.
+    fn confirmations(&self) -> u64 {
+        self.current_chain_config()
+            .checkpoint
+            .confirmations
+            .unwrap_or(DEFAULT_CONFIRMATIONS)
+            .max(1)
+    }
+
+    /// Reports the listener position to the metrics and the health endpoints.
+    fn report_heads(&self, indexed_head: u64, chain_head: u64) {
+        metrics().set_heads(&self.chain_config.name, indexed_head, chain_head);
+        health().record_heads(&self.service_name(), indexed_head, chain_head);
+        self.report_success();
 
+        let lag = chain_head.saturating_sub(indexed_head);
+        let max_lag_blocks = self
+            .current_chain_config()
+            .health
+            .max_lag_blocks
+            .unwrap_or(DEFAULT_MAX_LAG_BLOCKS);
+        let lagging = lag > max_lag_blocks;
+        if lagging && !self.lagging.swap(lagging, Ordering::Relaxed) {
+            alert::raise(Alert::new(
+                Severity::Warning,
+                "listener_lagging",
+                &self.chain_config.name,
+                format!("Listener is {lag} blocks behind the chain head {chain_head}"),
+            ));
+        } else if !lagging {
+            self.lagging.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// First block to index, from the last checkpoint or the configured starting block.
+    async fn starting_block(&self) -> Result<u64> {
         // Take the starting block height from the database, check if it is coherent with the configured starting block
         let config_starting_block = self.chain_config.start_block;
         let db_starting_block = self
             .block_checkpoint_repository
-            .get_last_block_checkpoint()
+            .get_last_block_checkpoint(self.chain_config.chain_id)
             .await?
             .map(|checkpoint| (checkpoint.height as u64) + 1);
 
-        let mut from_block = match (config_starting_block, db_starting_block) {
+        match (config_starting_block, db_starting_block) {
             (Some(config_starting_block), Some(db_starting_block)) => {
                 if db_starting_block < config_starting_block {
+                    alert::raise(Alert::new(
+                        Severity::Critical,
+                        "checkpoint_inconsistency",
+                        &self.chain_config.name,
+                        format!(
+                            "Listener would resume at block {db_starting_block}, before the \
+                            configured start_block {config_starting_block}, it cannot start"
+                        ),
+                    ));
                     return Err(eyre::eyre!(
                         "Starting block from the database is less than the configured starting block. \
                         Orders may remain stuck forever. Please fix the database inconsistency."
                     ));
                 }
 
-                db_starting_block
+                Ok(db_starting_block)
             }
-            (None, Some(db_starting_block)) => db_starting_block,
-            (Some(config_starting_block), None) => config_starting_block,
-            (None, None) => {
-                return Ok(());
-            }
-        };
+            (None, Some(db_starting_block)) => Ok(db_starting_block),
+            (Some(config_starting_block), None) => Ok(config_starting_block),
+            // TODO decide whether to work with Safe or Finalized or Latest block
+            (None, None) => Ok(self.rpc_pool.latest_block().await? + 1),
+        }
+    }
 
-        let provider = ProviderBuilder::new().on_builtin(url).await?;
+    /// Indexes the logs of `from_block..=to_block` over HTTP, `to_block` being the chain head.
+    /// The checkpoint stays `confirmations` blocks behind it, so reorged blocks are indexed
+    /// again after a restart.
+    async fn backfill(&self, from_block: u64, to_block: u64) -> Result<()> {
+        self.index_range(from_block, to_block, true).await
+    }
 
-        // TODO decide whether to work with Safe or Finalized or Latest block
-        let latest_block = provider
-            .get_block_by_number(
-                alloy::eips::BlockNumberOrTag::Latest,
-                BlockTransactionsKind::Full,
-            )
-            .await?
-            .expect("Latest block must exist!")
-            .header
-            .number;
+    /// Indexes the logs of `from_block..=to_block` again, leaving the checkpoint where it is.
+    async fn reindex(&self, from_block: u64, to_block: u64) -> Result<()> {
+        self.index_range(from_block, to_block, false).await
+    }
+
+    async fn index_range(&self, from_block: u64, to_block: u64, checkpoint: bool) -> Result<()> {
+        if from_block > to_block {
+            return Ok(());
+        }
+        let address: Address = self.chain_config.order_contract_address.parse()?;
+        let block_batch_size = self.chain_config.block_batch_size.unwrap_or(1_000);
+        let confirmed_block = to_block.saturating_sub(self.confirmations());
 
         debug!(
-            url, %address, from_block, latest_block,
-            "Polling routine starting!"
+            %address, from_block, to_block,
+            "Back-fill starting!"
         );
 
-        while from_block <= latest_block {
-            let to_block = block_batch_size + from_block;
-            let to_block = std::cmp::min(to_block, latest_block);
-            debug!(from_block, to_block, "Processing block batch");
-
-            let filter = Filter::new()
-                .address(address)
-                .events([
-                    Orderbook::OrderCreated::SIGNATURE,
-                    Orderbook::OrderWithdrawn::SIGNATURE,
-                    Orderbook::OrderFilled::SIGNATURE,
-                ])
-                .from_block(from_block)
-                .to_block(to_block);
-
-            // TODO index add chain_id column to the order table
+        let mut persisted_range_size = self
+            .chain_state_repository
+            .get_chain_state(self.chain_config.chain_id)
+            .await?
+            .and_then(|state| state.log_range_size)
+            .map(|size| size as u64);
+        let mut log_range = LogRange::new(
+            persisted_range_size.unwrap_or(block_batch_size),
+            block_batch_size,
+        );
+        let filter = Filter::new().address(address).events(INDEXED_EVENTS);
+
+        let mut from_block = from_block;
+        while from_block <= to_block {
+            debug!(
+                from_block,
+                range_size = log_range.size(),
+                "Processing block batch"
+            );
+
             // TODO should we use a db tx?
-            let sub = provider.get_logs(&filter).await?;
+            let (sub, batch_to_block) = get_logs_adaptive(
+                |filter| {
+                    self.rpc_pool.call(move |provider| {
+                        let filter = filter.clone();
+                        async move { provider.get_logs(&filter).await }
+                    })
+                },
+                &filter,
+                from_block,
+                to_block,
+                &mut log_range,
+            )
+            .await?;
+            if persisted_range_size != Some(log_range.size()) {
+                self.chain_state_repository
+                    .set_log_range_size(self.chain_config.chain_id, log_range.size())
+                    .await?;
+                persisted_range_size = Some(log_range.size());
+            }
             for log in sub {
                 self.process_event_log(&log).await?;
             }
 
-            debug!(from_block, to_block, latest_block, "Block batch processed!");
+            debug!(
+                from_block,
+                batch_to_block, to_block, "Block batch processed!"
+            );
 
-            self.block_checkpoint_repository
-                .create_block_checkpoint(self.chain_config.chain_id, to_block)
-                .await?;
-            from_block = to_block + 1;
+            if checkpoint {
+                let checkpoint_block = batch_to_block.min(confirmed_block);
+                if checkpoint_block >= from_block {
+                    self.block_checkpoint_repository
+                        .set_block_checkpoint(self.chain_config.chain_id, checkpoint_block)
+                        .await?;
+                }
+                self.report_heads(batch_to_block, to_block);
+            }
+            from_block = batch_to_block + 1;
         }
 
         Ok(())
     }
 
-    async fn run_subscription(&self) -> Result<()> {
-        let url = &self.chain_config.ws_url;
+    /// Opens log and new head subscriptions whose events are buffered until they are consumed.
+    async fn subscribe(&self) -> Result<LiveSubscription> {
+        let url = self
+            .rpc_pool
+            .ws_url()
+            .ok_or(eyre::eyre!("No WebSocket endpoint configured"))?;
         let address: Address = self.chain_config.order_contract_address.parse()?;
+        let filter = Filter::new().address(address).events(INDEXED_EVENTS);
 
-        let starting_block = self
-            .block_checkpoint_repository
-            .get_last_block_checkpoint()
-            .await?
-            .map(|checkpoint| BlockNumberOrTag::Number(checkpoint.height as u64 + 1))
-            .unwrap_or(BlockNumberOrTag::Finalized); // TODO: define if this should be Finalized or Latest
+        let provider = ProviderBuilder::new()
+            .on_builtin(&url)
+            .await
+            .inspect_err(|_| self.rpc_pool.record_failure(&url))?;
+        let mut logs = provider
+            .subscribe_logs(&filter)
+            .await
+            .inspect_err(|_| self.rpc_pool.record_failure(&url))?;
+        let mut heads = provider
+            .subscribe_blocks()
+            .await
+            .inspect_err(|_| self.rpc_pool.record_failure(&url))?;
 
-        info!(
-            url, %address, ?starting_block,
-            "Subscription worker routine is starting!"
-        );
+        info!(url = redact_url(&url), %address, "Subscribed to logs and new heads");
 
-        let filter = Filter::new()
-            .address(address)
-            .events([
-                Orderbook::OrderCreated::SIGNATURE,
-                Orderbook::OrderWithdrawn::SIGNATURE,
-                Orderbook::OrderFilled::SIGNATURE,
-            ])
-            .from_block(starting_block);
+        // The provider is moved along so the connection lives as long as the subscriptions,
+        // both end as soon as one of them closes
+        let (sender, events) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let _provider = provider;
+            loop {
+                // Logs first, the heads of their blocks may be waiting as well
+                let event = tokio::select! {
+                    biased;
+                    log = logs.recv() => log.map(|log| LiveEvent::Log(Box::new(log))),
+                    head = heads.recv() => head.map(|head| LiveEvent::Head(head.number)),
+                };
+                match event {
+                    Ok(event) => {
+                        if sender.send(event).is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        warn!(error = %e, "Subscription closed");
+                        break;
+                    }
+                }
+            }
+        });
 
-        let provider = ProviderBuilder::new().on_builtin(url).await?;
-        let mut sub = provider.subscribe_logs(&filter).await?;
+        Ok(LiveSubscription { url, events })
+    }
 
-        loop {
-            let log = sub.recv().await?;
-            self.process_event_log(&log).await?;
-            match log.block_number {
-                Some(n) => {
-                    self.block_checkpoint_repository
-                        .create_block_checkpoint(self.chain_config.chain_id, n)
-                        .await?
+    async fn has_pending_controls(&self) -> Result<bool> {
+        Ok(self
+            .chain_state_repository
+            .get_chain_state(self.chain_config.chain_id)
+            .await?
+            .is_some_and(|state| state.listener_paused || state.reindex_from_block.is_some()))
+    }
+
+    /// Waits while the listener is paused, then runs the pending re-index request, if any.
+    async fn apply_controls(&self, cursor: &mut LogCursor, poll_interval: u64) -> Result<()> {
+        while self
+            .chain_state_repository
+            .get_chain_state(self.chain_config.chain_id)
+            .await?
+            .is_some_and(|state| state.listener_paused)
+        {
+            debug!(chain_name = self.chain_config.name, "Listener paused");
+            self.report_success();
+            tokio::time::sleep(tokio::time::Duration::from_millis(poll_interval)).await;
+        }
+
+        let Some((from_block, to_block)) = self
+            .chain_state_repository
+            .get_reindex_request(self.chain_config.chain_id)
+            .await?
+        else {
+            return Ok(());
+        };
+        match to_block {
+            // Blocks past the cursor are indexed as usual
+            Some(to_block) => {
+                let to_block = to_block.min(cursor.next_block.saturating_sub(1));
+                info!(from_block, to_block, "Re-indexing block range");
+                self.reindex(from_block, to_block).await?;
+            }
+            None => {
+                info!(from_block, "Moving the cursor to a new block");
+                cursor.reset(from_block);
+            }
+        }
+        self.chain_state_repository
+            .clear_reindex_request(self.chain_config.chain_id, from_block, to_block)
+            .await
+    }
+
+    /// Processes live events until the subscription drops. Logs of blocks that were back-filled
+    /// already are skipped, and blocks the subscription did not deliver are back-filled first.
+    /// New heads move the checkpoint forward, even when no log shows up, once the logs of the
+    /// confirmed blocks were fetched, and the blocks of a reorg are rolled back and back-filled
+    /// from the canonical chain. Returns once an
+    /// operator paused the listener or requested a re-index.
+    async fn run_subscription(
+        &self,
+        subscription: &mut LiveSubscription,
+        cursor: &mut LogCursor,
+    ) -> Result<()> {
+        let mut checkpoints = CheckpointThrottle::new(&self.current_chain_config().checkpoint);
+        while let Some(event) = subscription.events.recv().await {
+            let log = match event {
+                LiveEvent::Log(log) => *log,
+                LiveEvent::Head(block_number) => {
+                    let completed_block = block_number.saturating_sub(self.confirmations());
+                    // Logs the subscription has not delivered yet are fetched, so the cursor
+                    // never moves past a block whose logs were not processed
+                    if let Some((from_block, to_block)) = cursor.missing_blocks(completed_block) {
+                        self.index_range(from_block, to_block, false).await?;
+                        cursor.backfilled(to_block);
+                    }
+                    self.report_heads(completed_block, block_number);
+                    if self.has_pending_controls().await? {
+                        info!("Operator request pending, leaving the subscription");
+                        return Ok(());
+                    }
+                    if checkpoints.is_due(completed_block, Instant::now()) {
+                        self.block_checkpoint_repository
+                            .set_block_checkpoint(self.chain_config.chain_id, completed_block)
+                            .await?;
+                        checkpoints.written(completed_block, Instant::now());
+                    }
+                    continue;
                 }
-                None => warn!(?log, "No block number found for log"),
+            };
+            let (Some(block_number), Some(log_index)) = (log.block_number, log.log_index) else {
+                warn!(?log, "No block number found for log");
+                continue;
+            };
+
+            if log.removed {
+                warn!(block_number, "Log removed by a reorg, rolling back");
+                self.order_event_repository
+                    .rollback_from_block(self.chain_config.chain_id, block_number)
+                    .await?;
+                self.block_checkpoint_repository
+                    .set_block_checkpoint(
+                        self.chain_config.chain_id,
+                        block_number.saturating_sub(1),
+                    )
+                    .await?;
+                cursor.rewind(block_number);
+                checkpoints.written(block_number.saturating_sub(1), Instant::now());
+
+                // The replacing blocks may have been announced before the removal, so their
+                // logs are fetched again rather than awaited from the subscription
+                let head = self.rpc_pool.latest_block().await?;
+                self.backfill(block_number, head).await?;
+                cursor.backfilled(head);
+                continue;
+            }
+            if !cursor.is_new(block_number, log_index) {
+                debug!(block_number, log_index, "Log already indexed, skipping");
+                continue;
             }
+            if block_number > cursor.next_block {
+                self.backfill(cursor.next_block, block_number - 1).await?;
+            }
+
+            self.process_event_log(&log).await?;
+            cursor.advance(block_number, log_index);
+        }
+
+        Err(eyre::eyre!("Subscription disconnected"))
+    }
+}
+
+enum LiveEvent {
+    Log(Box<Log>),
+    Head(u64),
+}
+
+/// Live logs and heads, buffered while the back-fill runs.
+struct LiveSubscription {
+    url: String,
+    events: mpsc::UnboundedReceiver<LiveEvent>,
+}
+
+/// Decides when a new head is worth a checkpoint write.
+struct CheckpointThrottle {
+    interval_blocks: u64,
+    interval: Option<Duration>,
+    last_written: Option<(u64, Instant)>,
+}
+
+impl CheckpointThrottle {
+    fn new(config: &CheckpointConfig) -> Self {
+        Self {
+            interval_blocks: config.interval_blocks.unwrap_or(1).max(1),
+            interval: config.interval_seconds.map(Duration::from_secs),
+            last_written: None,
+        }
+    }
+
+    fn is_due(&self, block_number: u64, now: Instant) -> bool {
+        let Some((last_block, last_at)) = self.last_written else {
+            return true;
+        };
+        if block_number <= last_block {
+            return false;
         }
+        block_number - last_block >= self.interval_blocks
+            || self
+                .interval
+                .is_some_and(|interval| now.duration_since(last_at) >= interval)
+    }
+
+    fn written(&mut self, block_number: u64, now: Instant) {
+        self.last_written = Some((block_number, now));
+    }
+}
+
+/// Position of the ingestion, shared by back-fills and the subscription so that every log is
+/// processed exactly once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LogCursor {
+    /// First block that has not been back-filled nor seen on the subscription.
+    next_block: u64,
+    /// Last live log processed, as its block number and log index.
+    last_log: Option<(u64, u64)>,
+}
+
+impl LogCursor {
+    fn new(next_block: u64) -> Self {
+        Self {
+            next_block,
+            last_log: None,
+        }
+    }
+
+    fn is_new(&self, block_number: u64, log_index: u64) -> bool {
+        match self.last_log {
+            Some((last_block, last_index)) if block_number == last_block => log_index > last_index,
+            _ => block_number >= self.next_block,
+        }
+    }
+
+    fn backfilled(&mut self, to_block: u64) {
+        self.next_block = self.next_block.max(to_block + 1);
+    }
+
+    /// Blocks up to `completed_block` the cursor has not moved past yet. The log and head
+    /// subscriptions are not ordered, so their logs may still be on the way or lost.
+    fn missing_blocks(&self, completed_block: u64) -> Option<(u64, u64)> {
+        (self.next_block <= completed_block).then_some((self.next_block, completed_block))
+    }
+
+    /// Later logs of the same block still come through the subscription.
+    fn advance(&mut self, block_number: u64, log_index: u64) {
+        self.next_block = block_number + 1;
+        self.last_log = Some((block_number, log_index));
+    }
+
+    /// Moves the cursor to `next_block`, forward or backward.
+    fn reset(&mut self, next_block: u64) {
+        self.next_block = next_block;
+        self.last_log = None;
+    }
+
+    fn rewind(&mut self, block_number: u64) {
+        self.next_block = self.next_block.min(block_number);
+        self.last_log = None;
     }
 }
 
 impl Service for Listener {
     async fn _run(&self) -> Result<()> {
-        self.run_polling().await?;
-        self.run_subscription().await
+        let mut cursor = LogCursor::new(self.starting_block().await?);
+
+        loop {
+            let poll_interval = self
+                .current_chain_config()
+                .listener_poll_interval
+                .unwrap_or(DEFAULT_LISTENER_POLL_INTERVAL);
+            self.apply_controls(&mut cursor, poll_interval).await?;
+            match self.subscribe().await {
+                Ok(mut subscription) => {
+                    // Blocks after this head are delivered by the subscription opened before it
+                    let head = self.rpc_pool.latest_block().await?;
+                    self.backfill(cursor.next_block, head).await?;
+                    cursor.backfilled(head);
+
+                    match self.run_subscription(&mut subscription, &mut cursor).await {
+                        Ok(()) => continue,
+                        Err(e) => {
+                            warn!(error = %e, "Log subscription lost, falling back to polling");
+                            self.rpc_pool.record_failure(&subscription.url);
+                        }
+                    }
+                }
+                Err(e) => warn!(error = %e, "Unable to subscribe to logs, polling instead"),
+            }
+
+            let head = self.rpc_pool.latest_block().await?;
+            self.backfill(cursor.next_block, head).await?;
+            cursor.backfilled(head);
+            self.report_heads(head, head);
+            tokio::time::sleep(tokio::time::Duration::from_millis(poll_interval)).await;
+        }
     }
 
     fn service_name(&self) -> String {
         format!("{} Listener", self.chain_config.name)
     }
+
+    fn chain_config(&self) -> &ChainConfig {
+        &self.chain_config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{CheckpointThrottle, LogCursor};
+    use crate::context::CheckpointConfig;
+
+    #[test]
+    fn test_log_cursor() {
+        let mut cursor = LogCursor::new(100);
+        assert!(!cursor.is_new(99, 0));
+        assert!(cursor.is_new(100, 0));
+
+        // Back-filled blocks are not processed again from the subscription
+        cursor.backfilled(120);
+        assert!(!cursor.is_new(120, 3));
+        assert!(cursor.is_new(121, 0));
+
+        cursor.advance(121, 2);
+        assert!(!cursor.is_new(121, 2));
+        assert!(cursor.is_new(121, 3));
+        assert!(cursor.is_new(122, 0));
+        assert_eq!(cursor.next_block, 122);
+
+        cursor.backfilled(110);
+        assert_eq!(cursor.next_block, 122);
+
+        cursor.rewind(115);
+        assert_eq!(cursor.next_block, 115);
+        assert!(cursor.is_new(115, 0));
+
+        cursor.reset(200);
+        assert_eq!(cursor.next_block, 200);
+        assert!(!cursor.is_new(199, 0));
+    }
+
+    #[test]
+    fn test_late_log_after_head() {
+        let mut cursor = LogCursor::new(100);
+
+        // The head of block 101 comes in before the logs of blocks 100 and 101
+        assert_eq!(cursor.missing_blocks(99), None);
+        assert!(cursor.is_new(100, 0));
+        cursor.advance(100, 0);
+        assert!(cursor.is_new(101, 4));
+        cursor.advance(101, 4);
+        assert_eq!(cursor.missing_blocks(100), None);
+        assert!(!cursor.is_new(101, 4));
+        assert!(cursor.is_new(102, 0));
+
+        // Logs more than the confirmations late are fetched before the cursor moves on
+        assert_eq!(cursor.missing_blocks(104), Some((102, 104)));
+        cursor.backfilled(104);
+        assert_eq!(cursor.missing_blocks(104), None);
+        assert!(!cursor.is_new(103, 0));
+        assert!(cursor.is_new(105, 0));
+    }
+
+    #[test]
+    fn test_checkpoint_throttle() {
+        let now = Instant::now();
+        let mut throttle = CheckpointThrottle::new(&CheckpointConfig {
+            interval_blocks: Some(10),
+            interval_seconds: Some(30),
+            confirmations: None,
+        });
+        assert!(throttle.is_due(100, now));
+        throttle.written(100, now);
+
+        assert!(!throttle.is_due(100, now));
+        assert!(!throttle.is_due(109, now));
+        assert!(throttle.is_due(110, now));
+        assert!(throttle.is_due(101, now + Duration::from_secs(30)));
+
+        let mut every_block = CheckpointThrottle::new(&CheckpointConfig::default());
+        every_block.written(100, now);
+        assert!(every_block.is_due(101, now));
+    }
 }
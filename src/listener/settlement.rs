@@ -0,0 +1,177 @@
+use alloy::{
+    consensus::Transaction,
+    primitives::{Address, TxHash},
+    providers::{ext::DebugApi, Provider},
+    rpc::types::trace::geth::{CallConfig, CallFrame, GethDebugTracingOptions},
+};
+use entity::order_settlement;
+use eyre::Result;
+use sea_orm::ActiveValue;
+use tracing::{info, warn};
+
+use crate::solidity::{
+    decode_receive_message, find_receive_message, receive_message_settles,
+    Orderbook::receiveMessageCall,
+};
+
+/// JSON-RPC error code of a method the node does not serve.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Call trace of the last traced settlement transaction, a transaction settling a batch of
+/// orders emits one `OrderFilled` per order.
+#[derive(Default)]
+pub(super) struct TraceCache {
+    /// Set once an endpoint answered that it does not serve `debug_traceTransaction`.
+    unsupported: bool,
+    last: Option<(TxHash, Option<CallFrame>)>,
+}
+
+impl super::Listener {
+    /// Looks up the `receiveMessage` call that delivered the settlement of an order, either
+    /// as the transaction itself or, when it went through a dispatcher, in its call trace.
+    async fn get_receive_message(
+        &self,
+        order_id: &[u8],
+        log: &alloy::rpc::types::Log,
+    ) -> Result<Option<receiveMessageCall>> {
+        let tx_hash = log
+            .transaction_hash
+            .ok_or(eyre::eyre!("No transaction hash found for log"))?;
+        let orderbook: Address = self.chain_config.order_contract_address.parse()?;
+        let tx = self
+            .rpc_pool
+            .call(|provider| async move { provider.get_transaction_by_hash(tx_hash).await })
+            .await?
+            .ok_or(eyre::eyre!("Transaction {tx_hash} not found"))?;
+        if tx.to() == Some(orderbook) {
+            if let Some(call) = decode_receive_message(tx.input())
+                .filter(|call| receive_message_settles(call, order_id))
+            {
+                return Ok(Some(call));
+            }
+        }
+
+        if !self.current_chain_config().trace_settlements {
+            return Ok(None);
+        }
+        let frame = self.trace_settlement(tx_hash).await;
+        Ok(frame.and_then(|frame| find_receive_message(&frame, orderbook, order_id)))
+    }
+
+    async fn trace_settlement(&self, tx_hash: TxHash) -> Option<CallFrame> {
+        {
+            let cache = self.settlement_traces.lock().unwrap();
+            if cache.unsupported {
+                return None;
+            }
+            if let Some((cached_hash, frame)) = &cache.last {
+                if *cached_hash == tx_hash {
+                    return frame.clone();
+                }
+            }
+        }
+
+        let trace = self
+            .rpc_pool
+            .call(|provider| async move {
+                provider
+                    .debug_trace_transaction(
+                        tx_hash,
+                        GethDebugTracingOptions::call_tracer(CallConfig::default()),
+                    )
+                    .await
+            })
+            .await;
+        let frame = match trace {
+            Ok(trace) => trace.try_into_call_frame().ok(),
+            Err(e) => {
+                let unsupported = e
+                    .as_error_resp()
+                    .is_some_and(|error| error.code == METHOD_NOT_FOUND);
+                warn!(%tx_hash, error = %e, unsupported, "Unable to trace settlement transaction");
+                if unsupported {
+                    self.settlement_traces.lock().unwrap().unsupported = true;
+                }
+                return None;
+            }
+        };
+        self.settlement_traces.lock().unwrap().last = Some((tx_hash, frame.clone()));
+        frame
+    }
+
+    /// Records the settlement of an order, closing the loop opened by the fill on the
+    /// destination chain.
+    pub(super) async fn process_settlement(
+        &self,
+        order_id: Vec<u8>,
+        filler: Address,
+        log: &alloy::rpc::types::Log,
+    ) -> Result<()> {
+        let tx_hash = log
+            .transaction_hash
+            .ok_or(eyre::eyre!("No transaction hash found for log"))?;
+        let block_number = log
+            .block_number
+            .ok_or(eyre::eyre!("No block number found for log"))?;
+        let settled_at = log
+            .block_timestamp
+            .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp as i64, 0))
+            .unwrap_or_else(chrono::Utc::now)
+            .naive_utc();
+        let paid_to_us = self.live_config().filler_addresses.contains(&filler);
+
+        let receive_message = self.get_receive_message(&order_id, log).await?;
+        if receive_message.is_none() {
+            warn!(
+                order_id = hex::encode(&order_id),
+                %tx_hash,
+                "No settlement message of the order found in the transaction"
+            );
+        }
+
+        let mut settlement = order_settlement::ActiveModel {
+            id: ActiveValue::NotSet,
+            order_id: ActiveValue::set(order_id.clone()),
+            chain_id: ActiveValue::set(self.chain_config.chain_id as i64),
+            tx_hash: ActiveValue::set(tx_hash.to_vec()),
+            block_number: ActiveValue::set(block_number as i64),
+            filler: ActiveValue::set(filler.to_vec()),
+            paid_to_us: ActiveValue::set(paid_to_us),
+            settled_at: ActiveValue::set(settled_at),
+            dispatcher: ActiveValue::set(None),
+            message_block_number: ActiveValue::set(None),
+            block_confirmations: ActiveValue::set(None),
+            message_sender: ActiveValue::set(None),
+            message_receiver: ActiveValue::set(None),
+            hashed_data: ActiveValue::set(None),
+            message_data: ActiveValue::set(None),
+        };
+        if let Some(call) = receive_message {
+            settlement.dispatcher = ActiveValue::set(Some(call.dispatcher.to_vec()));
+            settlement.message_block_number =
+                ActiveValue::set(Some(call.message.blockNumber.saturating_to::<i64>()));
+            settlement.block_confirmations =
+                ActiveValue::set(Some(call.message.blockConfirmations.into()));
+            settlement.message_sender = ActiveValue::set(Some(
+                [call.message.sender.lower, call.message.sender.upper].concat(),
+            ));
+            settlement.message_receiver = ActiveValue::set(Some(
+                [call.message.receiver.lower, call.message.receiver.upper].concat(),
+            ));
+            settlement.hashed_data = ActiveValue::set(Some(call.message.hashedData.to_vec()));
+            settlement.message_data = ActiveValue::set(Some(call.messageData.to_vec()));
+        }
+
+        self.order_settlement_repository
+            .create_settlement(settlement)
+            .await?;
+
+        info!(
+            order_id = hex::encode(&order_id),
+            %filler,
+            paid_to_us,
+            "Order settlement recorded"
+        );
+        Ok(())
+    }
+}
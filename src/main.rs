@@ -1,43 +1,69 @@
+use clap::Parser;
+use cli::{Cli, Command, ConfigCommand, OrderCommand};
 use dotenv::dotenv;
 use eyre::Result;
-use filler::Filler;
-use service::Service;
-use tokio::{self, task::JoinSet};
-use tracing::info;
-use tracing_subscriber::EnvFilter;
 
+mod alert;
+mod api;
+mod cli;
 mod context;
 mod filler;
+mod health;
 mod listener;
+mod metrics;
+mod multicall;
+mod reconciler;
 mod repository;
+mod rpc;
+mod runtime;
+mod schema;
+mod server;
 mod service;
 mod solidity;
+mod telemetry;
+mod token;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let env_filter = EnvFilter::try_from_default_env().or_else(|_| EnvFilter::try_new("info"))?;
-    tracing_subscriber::fmt().with_env_filter(env_filter).init();
-
+    let cli = Cli::parse();
     dotenv().ok();
 
-    let app_context = context::context()?;
-    info!("Bot is starting");
-
-    let mut join_set = JoinSet::new();
-
-    for chain in &app_context.config.chain {
-        info!(chain_name = chain.name, "Starting services");
-        let listener =
-            listener::Listener::new(app_context.config.postgres_url.clone(), chain.clone());
-        join_set.spawn(async move { listener.await.unwrap().run().await });
-
-        let filler = Filler::new(app_context.config.postgres_url.clone(), chain.clone());
-        join_set.spawn(async move { filler.await.unwrap().run().await });
+    // Runs before the configuration is loaded, which may need the file it writes
+    if let Some(Command::Config {
+        command: ConfigCommand::EncryptSecrets { input, output },
+    }) = &cli.command
+    {
+        return cli::encrypt_secrets(input, output);
     }
-
-    while let Some(res) = join_set.join_next().await {
-        res?;
+    let app_context = context::context()?;
+    let _telemetry = telemetry::init(&app_context.config.log)?;
+    let config = &app_context.config;
+    match cli.command.unwrap_or(Command::Run {
+        chains: vec![],
+        services: vec![],
+    }) {
+        Command::Run { chains, services } => {
+            config.check_chain_ids().await?;
+            schema::prepare(config.postgres_url.expose(), config.auto_migrate).await?;
+            runtime::run(app_context.config, chains, services).await
+        }
+        Command::Migrate { command } => cli::migrate(config, command).await,
+        Command::Reindex { chain, from, to } => cli::reindex(config, &chain, from, to).await,
+        Command::Order {
+            command: OrderCommand::Show { order_id },
+        } => cli::show_order(config, &order_id).await,
+        Command::DecodeLog { topics, data } => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&cli::decode_log(&topics, &data)?)?
+            );
+            Ok(())
+        }
+        Command::Config {
+            command: ConfigCommand::Check,
+        } => cli::check_config(config).await,
+        Command::Config {
+            command: ConfigCommand::EncryptSecrets { .. },
+        } => unreachable!("secrets are encrypted before the configuration is loaded"),
     }
-
-    Ok(())
 }
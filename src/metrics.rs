@@ -0,0 +1,250 @@
+use std::{sync::LazyLock, time::Duration};
+
+use eyre::Result;
+use prometheus::{
+    exponential_buckets, CounterVec, GaugeVec, HistogramOpts, HistogramVec, IntCounterVec,
+    IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use sea_orm::DatabaseConnection;
+
+static METRICS: LazyLock<Metrics> =
+    LazyLock::new(|| Metrics::new().expect("Metrics must be registered once"));
+
+/// Per-chain gauges and counters of the services, labelled with the chain name.
+pub struct Metrics {
+    registry: Registry,
+
+    pub indexed_head: IntGaugeVec,
+    pub chain_head: IntGaugeVec,
+    pub lag_blocks: IntGaugeVec,
+    pub logs_processed: IntCounterVec,
+    pub decode_failures: IntCounterVec,
+
+    pub ready_orders: IntGaugeVec,
+    pub fill_attempts: IntCounterVec,
+    pub fill_successes: IntCounterVec,
+    pub fill_failures: IntCounterVec,
+    /// In native tokens of the chain the fill happened on.
+    pub gas_spent: CounterVec,
+    /// In notional units.
+    pub profit_realised: CounterVec,
+    /// In native tokens.
+    pub wallet_balance: GaugeVec,
+
+    pub reconciler_drift: IntCounterVec,
+    pub service_restarts: IntCounterVec,
+    pub db_query_duration: HistogramVec,
+    pub alert_notifications: IntCounterVec,
+}
+
+impl Metrics {
+    fn new() -> Result<Self> {
+        let registry = Registry::new_custom(Some("ilayer_bot".to_string()), None)?;
+
+        let indexed_head = IntGaugeVec::new(
+            Opts::new("indexed_head", "Last block indexed by the listener"),
+            &["chain"],
+        )?;
+        let chain_head = IntGaugeVec::new(
+            Opts::new("chain_head", "Latest block seen by the listener"),
+            &["chain"],
+        )?;
+        let lag_blocks = IntGaugeVec::new(
+            Opts::new(
+                "lag_blocks",
+                "Blocks between the chain head and the indexed head",
+            ),
+            &["chain"],
+        )?;
+        let logs_processed = IntCounterVec::new(
+            Opts::new("logs_processed_total", "Orderbook logs processed"),
+            &["chain", "event"],
+        )?;
+        let decode_failures = IntCounterVec::new(
+            Opts::new("decode_failures_total", "Logs that could not be decoded"),
+            &["chain"],
+        )?;
+
+        let ready_orders = IntGaugeVec::new(
+            Opts::new("ready_orders", "Orders ready to be filled at the last poll"),
+            &["chain"],
+        )?;
+        let fill_attempts = IntCounterVec::new(
+            Opts::new(
+                "fill_attempts_total",
+                "Valid orders the filler tried to fill",
+            ),
+            &["chain"],
+        )?;
+        let fill_successes = IntCounterVec::new(
+            Opts::new("fill_successes_total", "Orders filled"),
+            &["chain"],
+        )?;
+        let fill_failures = IntCounterVec::new(
+            Opts::new("fill_failures_total", "Fill attempts that did not fill"),
+            &["chain", "reason"],
+        )?;
+        let gas_spent = CounterVec::new(
+            Opts::new(
+                "gas_spent_total",
+                "Native tokens spent on fill transactions",
+            ),
+            &["chain"],
+        )?;
+        let profit_realised = CounterVec::new(
+            Opts::new(
+                "profit_realised_total",
+                "Quoted profit of the filled orders",
+            ),
+            &["chain"],
+        )?;
+        let wallet_balance = GaugeVec::new(
+            Opts::new("wallet_balance", "Native balance of the filler wallet"),
+            &["chain", "address"],
+        )?;
+
+        let reconciler_drift = IntCounterVec::new(
+            Opts::new(
+                "reconciler_drift_total",
+                "Indexed orders whose status differed from the orderbook",
+            ),
+            &["chain", "kind"],
+        )?;
+        let service_restarts = IntCounterVec::new(
+            Opts::new("service_restarts_total", "Times a service was restarted"),
+            &["service"],
+        )?;
+        let db_query_duration = HistogramVec::new(
+            HistogramOpts::new("db_query_duration_seconds", "Duration of database queries")
+                .buckets(exponential_buckets(0.0005, 2.0, 14)?),
+            &["statement", "failed"],
+        )?;
+        let alert_notifications = IntCounterVec::new(
+            Opts::new(
+                "alert_notifications_total",
+                "Alerts sent to the notification sinks",
+            ),
+            &["sink", "status"],
+        )?;
+
+        registry.register(Box::new(indexed_head.clone()))?;
+        registry.register(Box::new(chain_head.clone()))?;
+        registry.register(Box::new(lag_blocks.clone()))?;
+        registry.register(Box::new(logs_processed.clone()))?;
+        registry.register(Box::new(decode_failures.clone()))?;
+        registry.register(Box::new(ready_orders.clone()))?;
+        registry.register(Box::new(fill_attempts.clone()))?;
+        registry.register(Box::new(fill_successes.clone()))?;
+        registry.register(Box::new(fill_failures.clone()))?;
+        registry.register(Box::new(gas_spent.clone()))?;
+        registry.register(Box::new(profit_realised.clone()))?;
+        registry.register(Box::new(wallet_balance.clone()))?;
+        registry.register(Box::new(reconciler_drift.clone()))?;
+        registry.register(Box::new(service_restarts.clone()))?;
+        registry.register(Box::new(db_query_duration.clone()))?;
+        registry.register(Box::new(alert_notifications.clone()))?;
+
+        Ok(Self {
+            registry,
+            indexed_head,
+            chain_head,
+            lag_blocks,
+            logs_processed,
+            decode_failures,
+            ready_orders,
+            fill_attempts,
+            fill_successes,
+            fill_failures,
+            gas_spent,
+            profit_realised,
+            wallet_balance,
+            reconciler_drift,
+            service_restarts,
+            alert_notifications,
+            db_query_duration,
+        })
+    }
+
+    /// Updates the listener heads, along with the lag between them.
+    pub fn set_heads(&self, chain: &str, indexed_head: u64, chain_head: u64) {
+        self.indexed_head
+            .with_label_values(&[chain])
+            .set(indexed_head as i64);
+        self.chain_head
+            .with_label_values(&[chain])
+            .set(chain_head as i64);
+        self.lag_blocks
+            .with_label_values(&[chain])
+            .set(chain_head.saturating_sub(indexed_head) as i64);
+    }
+
+    pub fn observe_query(&self, statement: &str, duration: Duration, failed: bool) {
+        self.db_query_duration
+            .with_label_values(&[
+                statement_kind(statement),
+                if failed { "true" } else { "false" },
+            ])
+            .observe(duration.as_secs_f64());
+    }
+
+    /// Renders all the metrics in the Prometheus text format.
+    pub fn encode(&self) -> Result<String> {
+        Ok(TextEncoder::new().encode_to_string(&self.registry.gather())?)
+    }
+}
+
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+/// Reports the duration of every query run on the connection.
+pub fn instrument_connection(connection: &mut DatabaseConnection) {
+    connection.set_metric_callback(|info| {
+        metrics().observe_query(&info.statement.sql, info.elapsed, info.failed);
+    });
+}
+
+/// Keeps the histogram labels bounded by only keeping the statement's leading keyword.
+fn statement_kind(sql: &str) -> &'static str {
+    let keyword = sql.split_whitespace().next().unwrap_or_default();
+    [
+        "SELECT", "INSERT", "UPDATE", "DELETE", "BEGIN", "COMMIT", "ROLLBACK",
+    ]
+    .into_iter()
+    .find(|kind| keyword.eq_ignore_ascii_case(kind))
+    .unwrap_or("OTHER")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{metrics, statement_kind};
+
+    #[test]
+    fn test_statement_kind() {
+        assert_eq!(
+            statement_kind(r#"SELECT "order"."id" FROM "order""#),
+            "SELECT"
+        );
+        assert_eq!(statement_kind("  insert into x values (1)"), "INSERT");
+        assert_eq!(
+            statement_kind("WITH x AS (SELECT 1) SELECT * FROM x"),
+            "OTHER"
+        );
+        assert_eq!(statement_kind(""), "OTHER");
+    }
+
+    #[test]
+    fn test_encode() -> eyre::Result<()> {
+        metrics().set_heads("test", 90, 100);
+        metrics().observe_query("SELECT 1", Duration::from_millis(3), false);
+
+        let encoded = metrics().encode()?;
+        assert!(encoded.contains(r#"ilayer_bot_lag_blocks{chain="test"} 10"#));
+        assert!(encoded.contains(
+            r#"ilayer_bot_db_query_duration_seconds_count{failed="false",statement="SELECT"}"#
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,573 @@
+use std::ops::Range;
+
+use alloy::{
+    primitives::{address, Address, Bytes},
+    providers::Provider,
+    rpc::types::TransactionRequest,
+    sol,
+    sol_types::SolCall,
+    transports::TransportError,
+};
+use eyre::Result;
+use tracing::{debug, warn};
+
+/// Deployed at the same address on most EVM chains.
+pub const MULTICALL3_ADDRESS: Address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+
+const DEFAULT_MAX_CALLS: usize = 500;
+const DEFAULT_MAX_CALLDATA_SIZE: usize = 100_000;
+const DEFAULT_MAX_GAS: u64 = 25_000_000;
+const DEFAULT_CALL_GAS: u64 = 100_000;
+
+sol!(
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    interface IMulticall3 {
+        struct Call3 {
+            address target;
+            bool allowFailure;
+            bytes callData;
+        }
+
+        struct Result {
+            bool success;
+            bytes returnData;
+        }
+
+        function aggregate3(Call3[] calldata calls) external payable returns (Result[] memory returnData);
+    }
+);
+
+/// Bounds of a single `aggregate3` call, so batches stay under the node's `eth_call` caps.
+#[derive(Debug, Clone, Copy)]
+pub struct MulticallLimits {
+    pub max_calls: usize,
+    /// Total calldata of the batched calls, in bytes.
+    pub max_calldata_size: usize,
+    /// Sum of the gas hints of the batched calls.
+    pub max_gas: u64,
+}
+
+impl Default for MulticallLimits {
+    fn default() -> Self {
+        Self {
+            max_calls: DEFAULT_MAX_CALLS,
+            max_calldata_size: DEFAULT_MAX_CALLDATA_SIZE,
+            max_gas: DEFAULT_MAX_GAS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallFailure {
+    /// The call reverted, with its revert data when it is known.
+    Reverted(Bytes),
+    /// The call succeeded but its return data does not match the call.
+    Decode(String),
+    /// The call could not be made at all.
+    Rpc(String),
+}
+
+#[derive(Debug, Clone)]
+struct Call {
+    target: Address,
+    call_data: Bytes,
+    gas: u64,
+}
+
+/// Batches contract reads into `aggregate3` calls, falling back to one `eth_call` per read
+/// on chains without Multicall3.
+pub struct Multicall<'a, P> {
+    provider: &'a P,
+    address: Address,
+    limits: MulticallLimits,
+    calls: Vec<Call>,
+}
+
+impl<'a, P: Provider> Multicall<'a, P> {
+    pub fn new(provider: &'a P) -> Self {
+        Self {
+            provider,
+            address: MULTICALL3_ADDRESS,
+            limits: MulticallLimits::default(),
+            calls: vec![],
+        }
+    }
+
+    pub fn with_address(mut self, address: Address) -> Self {
+        self.address = address;
+        self
+    }
+
+    pub fn add_call<C: SolCall>(&mut self, target: Address, call: &C) -> &mut Self {
+        self.add_call_with_gas(target, call, DEFAULT_CALL_GAS)
+    }
+
+    /// Adds a call with a hint of the gas it uses, for calls heavier than the default.
+    pub fn add_call_with_gas<C: SolCall>(
+        &mut self,
+        target: Address,
+        call: &C,
+        gas: u64,
+    ) -> &mut Self {
+        self.calls.push(Call {
+            target,
+            call_data: call.abi_encode().into(),
+            gas,
+        });
+        self
+    }
+
+    /// Runs all the calls, returning their raw return data in the order they were added.
+    /// Only fails when the node cannot be reached, failed batches are retried one by one.
+    pub async fn call(self) -> Result<Vec<Result<Bytes, CallFailure>>, TransportError> {
+        if self.calls.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let has_multicall = !self.provider.get_code_at(self.address).await?.is_empty();
+        if !has_multicall {
+            debug!(multicall = %self.address, "Multicall3 not deployed, calling one by one");
+            return Ok(self.call_individually(&self.calls).await);
+        }
+
+        let mut results = Vec::with_capacity(self.calls.len());
+        for range in chunk_calls(&self.calls, &self.limits) {
+            let calls = &self.calls[range];
+            match self.aggregate(calls).await {
+                Ok(batch) => results.extend(batch),
+                Err(e) => {
+                    warn!(
+                        error = %e,
+                        calls = calls.len(),
+                        "Multicall3 batch failed, calling one by one"
+                    );
+                    results.extend(self.call_individually(calls).await);
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    async fn aggregate(&self, calls: &[Call]) -> Result<Vec<Result<Bytes, CallFailure>>> {
+        let calls3 = calls
+            .iter()
+            .map(|call| IMulticall3::Call3 {
+                target: call.target,
+                allowFailure: true,
+                callData: call.call_data.clone(),
+            })
+            .collect();
+        let multicall = IMulticall3::new(self.address, self.provider);
+        let results = multicall.aggregate3(calls3).call().await?.returnData;
+        if results.len() != calls.len() {
+            return Err(eyre::eyre!(
+                "Multicall3 returned {} results for {} calls",
+                results.len(),
+                calls.len()
+            ));
+        }
+
+        Ok(results
+            .into_iter()
+            .map(|result| match result.success {
+                true => Ok(result.returnData),
+                false => Err(CallFailure::Reverted(result.returnData)),
+            })
+            .collect())
+    }
+
+    async fn call_individually(&self, calls: &[Call]) -> Vec<Result<Bytes, CallFailure>> {
+        let mut results = Vec::with_capacity(calls.len());
+        for call in calls {
+            let tx = TransactionRequest::default()
+                .to(call.target)
+                .input(call.call_data.clone().into());
+            let result = self
+                .provider
+                .call(&tx)
+                .await
+                .map_err(|e| match e.as_error_resp() {
+                    Some(payload) => {
+                        CallFailure::Reverted(payload.as_revert_data().unwrap_or_default())
+                    }
+                    None => CallFailure::Rpc(e.to_string()),
+                });
+            results.push(result);
+        }
+        results
+    }
+}
+
+/// Decodes the return data of a batched call.
+pub fn decode_return<C: SolCall>(
+    result: Result<Bytes, CallFailure>,
+) -> Result<C::Return, CallFailure> {
+    let return_data = result?;
+    C::abi_decode_returns(&return_data, true).map_err(|e| CallFailure::Decode(e.to_string()))
+}
+
+/// Runs the same kind of call against many targets, decoding each result.
+pub async fn aggregate<C: SolCall, P: Provider>(
+    mut multicall: Multicall<'_, P>,
+    calls: impl IntoIterator<Item = (Address, C)>,
+) -> Result<Vec<Result<C::Return, CallFailure>>, TransportError> {
+    for (target, call) in calls {
+        multicall.add_call(target, &call);
+    }
+    Ok(multicall
+        .call()
+        .await?
+        .into_iter()
+        .map(decode_return::<C>)
+        .collect())
+}
+
+/// Splits the calls into consecutive batches within the limits, a call that exceeds them on
+/// its own gets a batch of its own.
+fn chunk_calls(calls: &[Call], limits: &MulticallLimits) -> Vec<Range<usize>> {
+    let mut chunks = vec![];
+    let mut start = 0;
+    let mut calldata_size = 0;
+    let mut gas = 0;
+    for (i, call) in calls.iter().enumerate() {
+        let full = i - start >= limits.max_calls.max(1)
+            || calldata_size + call.call_data.len() > limits.max_calldata_size
+            || gas + call.gas > limits.max_gas;
+        if full && i > start {
+            chunks.push(start..i);
+            start = i;
+            calldata_size = 0;
+            gas = 0;
+        }
+        calldata_size += call.call_data.len();
+        gas += call.gas;
+    }
+    if start < calls.len() {
+        chunks.push(start..calls.len());
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        task::{Context, Poll},
+    };
+
+    use alloy::{
+        node_bindings::Anvil,
+        primitives::{Address, Bytes, U256},
+        providers::{ext::AnvilApi, Provider, ProviderBuilder},
+        rpc::{
+            client::RpcClient,
+            json_rpc::{ErrorPayload, RequestPacket, Response, ResponsePacket, ResponsePayload},
+        },
+        sol,
+        sol_types::SolCall,
+        transports::{BoxTransport, TransportError, TransportFut},
+    };
+    use serde_json::value::RawValue;
+
+    use super::{
+        aggregate, chunk_calls, decode_return, Call, CallFailure, IMulticall3, Multicall,
+        MulticallLimits, MULTICALL3_ADDRESS,
+    };
+
+    sol!(
+        #[allow(missing_docs)]
+        interface IAnswer {
+            function answer() external view returns (uint256);
+        }
+    );
+
+    /// Returns 42 to any call.
+    const ANSWER_CODE: &str = "0x602a60005260206000f3";
+    /// `aggregate3` as Multicall3 implements it for calls that allow failure, assembled by
+    /// hand as anvil has no Multicall3 without a fork.
+    const AGGREGATE3_CODE: &str = "0x600435600401600052600051356020526020610200526020516102\
+        2052602051602002610240016060525b60205160405110156100fa57604051602002600051016020\
+        013560005101602001608052608051604001356080510160a05260a0513560c05260c05160a05160\
+        2001606051606001376000600060c0516060516060016000608051355af160e0523d610100523d60\
+        006060516060013e600061010051606051016060015260e051606051526040606051602001526101\
+        0051606051604001526102406060510360405160200261024001526020601f610100510104602002\
+        6060016060510160605260405160010160405261002a565b61020060605103610200f3";
+    /// Reverts on any call.
+    const REVERT_CODE: &str = "0x60006000fd";
+
+    const ANSWER: Address = Address::repeat_byte(0x42);
+    const REVERTER: Address = Address::repeat_byte(0x43);
+    /// Returns no data, which does not decode as an answer.
+    const SILENT: Address = Address::repeat_byte(0x44);
+    const REVERT_DATA: &str = "0xdead";
+
+    /// Plays the node and the contracts: `ANSWER` returns 42, `REVERTER` reverts and
+    /// `SILENT` returns nothing, Multicall3 answers `aggregate3` unless `fail_aggregate`.
+    /// Keeps track of the number of calls made to each address.
+    #[derive(Clone)]
+    struct MockTransport {
+        has_multicall: bool,
+        fail_aggregate: bool,
+        calls: Arc<Mutex<Vec<Address>>>,
+    }
+
+    impl MockTransport {
+        fn new(has_multicall: bool, fail_aggregate: bool) -> Self {
+            Self {
+                has_multicall,
+                fail_aggregate,
+                calls: Arc::default(),
+            }
+        }
+
+        fn calls_to(&self, address: Address) -> usize {
+            let calls = self.calls.lock().unwrap();
+            calls.iter().filter(|call| **call == address).count()
+        }
+
+        fn answer(target: Address) -> IMulticall3::Result {
+            match target {
+                ANSWER => IMulticall3::Result {
+                    success: true,
+                    returnData: IAnswer::answerCall::abi_encode_returns(&(U256::from(42),)).into(),
+                },
+                REVERTER => IMulticall3::Result {
+                    success: false,
+                    returnData: REVERT_DATA.parse().unwrap(),
+                },
+                _ => IMulticall3::Result {
+                    success: true,
+                    returnData: Bytes::new(),
+                },
+            }
+        }
+
+        fn payload(&self, method: &str, params: serde_json::Value) -> ResponsePayload {
+            let success = |value: serde_json::Value| {
+                ResponsePayload::Success(RawValue::from_string(value.to_string()).unwrap())
+            };
+            let failure = |code, message: &str, data: Option<&str>| {
+                ResponsePayload::Failure(ErrorPayload {
+                    code,
+                    message: message.to_string().into(),
+                    data: data.map(|data| RawValue::from_string(format!("\"{data}\"")).unwrap()),
+                })
+            };
+            match method {
+                "eth_getCode" => success(if self.has_multicall { "0x01" } else { "0x" }.into()),
+                "eth_call" => {
+                    let tx = &params[0];
+                    let target: Address = tx["to"].as_str().unwrap().parse().unwrap();
+                    let input: Bytes = tx
+                        .get("input")
+                        .or(tx.get("data"))
+                        .and_then(|input| input.as_str())
+                        .unwrap()
+                        .parse()
+                        .unwrap();
+                    self.calls.lock().unwrap().push(target);
+
+                    if target == MULTICALL3_ADDRESS {
+                        if self.fail_aggregate {
+                            return failure(-32000, "out of gas", None);
+                        }
+                        let calls = IMulticall3::aggregate3Call::abi_decode(&input, true)
+                            .unwrap()
+                            .calls;
+                        let results: Vec<_> =
+                            calls.iter().map(|call| Self::answer(call.target)).collect();
+                        let output: Bytes =
+                            IMulticall3::aggregate3Call::abi_encode_returns(&(results,)).into();
+                        return success(output.to_string().into());
+                    }
+                    let result = Self::answer(target);
+                    match result.success {
+                        true => success(result.returnData.to_string().into()),
+                        false => failure(3, "execution reverted", Some(REVERT_DATA)),
+                    }
+                }
+                _ => unreachable!("unexpected method {method}"),
+            }
+        }
+    }
+
+    impl tower::Service<RequestPacket> for MockTransport {
+        type Response = ResponsePacket;
+        type Error = TransportError;
+        type Future = TransportFut<'static>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: RequestPacket) -> Self::Future {
+            let RequestPacket::Single(request) = request else {
+                unreachable!("batch requests are not used")
+            };
+            let params: serde_json::Value =
+                serde_json::from_str(request.params().unwrap().get()).unwrap();
+            let response = ResponsePacket::Single(Response {
+                id: request.id().clone(),
+                payload: self.payload(request.method(), params),
+            });
+            Box::pin(async move { Ok(response) })
+        }
+    }
+
+    fn provider(mock: MockTransport) -> impl Provider {
+        ProviderBuilder::new().on_client(RpcClient::new(BoxTransport::new(mock), true))
+    }
+
+    fn call(call_data_size: usize, gas: u64) -> Call {
+        Call {
+            target: Address::ZERO,
+            call_data: vec![0; call_data_size].into(),
+            gas,
+        }
+    }
+
+    #[test]
+    fn test_chunk_calls() {
+        let limits = MulticallLimits {
+            max_calls: 3,
+            max_calldata_size: 100,
+            max_gas: 1_000,
+        };
+
+        let calls: Vec<Call> = (0..7).map(|_| call(10, 100)).collect();
+        assert_eq!(chunk_calls(&calls, &limits), vec![0..3, 3..6, 6..7]);
+
+        let calls = vec![call(60, 100), call(60, 100), call(10, 100)];
+        assert_eq!(chunk_calls(&calls, &limits), vec![0..1, 1..3]);
+
+        let calls = vec![call(10, 600), call(10, 600), call(10, 2_000), call(10, 100)];
+        assert_eq!(chunk_calls(&calls, &limits), vec![0..1, 1..2, 2..3, 3..4]);
+
+        assert!(chunk_calls(&[], &limits).is_empty());
+    }
+
+    #[test]
+    fn test_decode_return() {
+        let return_data: Bytes = IAnswer::answerCall::abi_encode_returns(&(U256::from(42),)).into();
+        assert_eq!(
+            decode_return::<IAnswer::answerCall>(Ok(return_data))
+                .unwrap()
+                ._0,
+            U256::from(42)
+        );
+        assert!(matches!(
+            decode_return::<IAnswer::answerCall>(Ok(Bytes::new())),
+            Err(CallFailure::Decode(_))
+        ));
+        assert!(matches!(
+            decode_return::<IAnswer::answerCall>(Err(CallFailure::Reverted(Bytes::new()))),
+            Err(CallFailure::Reverted(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_results() -> eyre::Result<()> {
+        let targets = [ANSWER, REVERTER, SILENT, ANSWER];
+        // With Multicall3, from aggregate3 batches that failed and to single calls
+        for (has_multicall, fail_aggregate, aggregate3_calls, single_calls) in [
+            (true, false, 2, 0),
+            (true, true, 2, 4),
+            (false, false, 0, 4),
+        ] {
+            let mock = MockTransport::new(has_multicall, fail_aggregate);
+            let provider = provider(mock.clone());
+            let mut multicall = Multicall::new(&provider);
+            multicall.limits = MulticallLimits {
+                max_calls: 2,
+                ..Default::default()
+            };
+            let results = aggregate(
+                multicall,
+                targets
+                    .into_iter()
+                    .map(|target| (target, IAnswer::answerCall {})),
+            )
+            .await?;
+
+            assert_eq!(results.len(), 4);
+            assert_eq!(results[0].as_ref().unwrap()._0, U256::from(42));
+            let revert_data: Bytes = REVERT_DATA.parse()?;
+            assert!(
+                matches!(&results[1], Err(CallFailure::Reverted(data)) if *data == revert_data)
+            );
+            assert!(matches!(results[2], Err(CallFailure::Decode(_))));
+            assert_eq!(results[3].as_ref().unwrap()._0, U256::from(42));
+
+            assert_eq!(mock.calls_to(MULTICALL3_ADDRESS), aggregate3_calls);
+            let calls = mock.calls.lock().unwrap().len();
+            assert_eq!(calls - aggregate3_calls, single_calls);
+        }
+        Ok(())
+    }
+
+    async fn check_answers(install_multicall3: bool) -> eyre::Result<()> {
+        let anvil = Anvil::new().try_spawn()?;
+        let provider = ProviderBuilder::new().on_builtin(&anvil.endpoint()).await?;
+
+        let answer = Address::repeat_byte(0x42);
+        let reverter = Address::repeat_byte(0x43);
+        provider
+            .anvil_set_code(answer, ANSWER_CODE.parse()?)
+            .await?;
+        provider
+            .anvil_set_code(reverter, REVERT_CODE.parse()?)
+            .await?;
+        if install_multicall3 {
+            provider
+                .anvil_set_code(MULTICALL3_ADDRESS, AGGREGATE3_CODE.parse()?)
+                .await?;
+        }
+
+        let mut multicall = Multicall::new(&provider);
+        multicall.limits = MulticallLimits {
+            max_calls: 2,
+            ..Default::default()
+        };
+        let targets = [answer, reverter, answer];
+        if install_multicall3 {
+            // Batches must go through aggregate3 rather than fall back to single calls
+            let calls = targets
+                .iter()
+                .map(|target| Call {
+                    target: *target,
+                    call_data: IAnswer::answerCall {}.abi_encode().into(),
+                    gas: 0,
+                })
+                .collect::<Vec<_>>();
+            let batch = multicall.aggregate(&calls).await?;
+            assert!(batch[0].is_ok());
+            assert!(matches!(batch[1], Err(CallFailure::Reverted(_))));
+        }
+        let results = aggregate(
+            multicall,
+            targets
+                .into_iter()
+                .map(|target| (target, IAnswer::answerCall {})),
+        )
+        .await?;
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap()._0, U256::from(42));
+        assert!(matches!(results[1], Err(CallFailure::Reverted(_))));
+        assert_eq!(results[2].as_ref().unwrap()._0, U256::from(42));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_fallback_without_multicall3() -> eyre::Result<()> {
+        check_answers(false).await
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_aggregate_with_multicall3() -> eyre::Result<()> {
+        check_answers(true).await
+    }
+}
@@ -0,0 +1,256 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use alloy::primitives::{Address, FixedBytes};
+use entity::{order, sea_orm_active_enums::OrderStatus};
+use eyre::Result;
+use tracing::{info, warn};
+
+use crate::{
+    context::ChainConfig,
+    metrics::metrics,
+    multicall::{aggregate, Multicall},
+    repository::order::OrderRepository,
+    rpc::RpcPool,
+    runtime::{LiveConfig, LiveConfigReceiver},
+    service::Service,
+    solidity::{onchain_status_to_order_status, Orderbook},
+};
+
+const DEFAULT_RECONCILE_INTERVAL: u64 = 60_000;
+const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// How the indexed status of an order compares with the orderbook.
+#[derive(Debug, PartialEq, Eq)]
+enum Drift {
+    None,
+    /// The on-chain status could not be read.
+    Unreadable,
+    /// The orderbook has the order in another status.
+    Status(OrderStatus),
+    /// The orderbook does not know the order, with the raw status it returned.
+    Unknown(u8),
+}
+
+fn drift(indexed_status: &OrderStatus, onchain_status: Option<u8>) -> Drift {
+    let Some(onchain_status) = onchain_status else {
+        return Drift::Unreadable;
+    };
+    match onchain_status_to_order_status(onchain_status) {
+        Some(status) if status == *indexed_status => Drift::None,
+        Some(status) => Drift::Status(status),
+        None => Drift::Unknown(onchain_status),
+    }
+}
+
+/// Brings indexed order statuses back in line with the orderbook, for orders whose
+/// status change was never indexed.
+pub(crate) struct Reconciler {
+    chain_config: ChainConfig,
+    rpc_pool: Arc<RpcPool>,
+    live_config: LiveConfigReceiver,
+    order_repository: Arc<OrderRepository>,
+    drift_total: AtomicU64,
+}
+
+impl Reconciler {
+    pub async fn new(
+        postgres_url: String,
+        chain_config: ChainConfig,
+        rpc_pool: Arc<RpcPool>,
+        live_config: LiveConfigReceiver,
+    ) -> Result<Self> {
+        let order_repository = Arc::new(OrderRepository::new(postgres_url).await?);
+        Ok(Self {
+            chain_config,
+            rpc_pool,
+            live_config,
+            order_repository,
+            drift_total: AtomicU64::new(0),
+        })
+    }
+
+    /// Configuration of the chain as of the last reload.
+    fn current_chain_config(&self) -> ChainConfig {
+        let live_config: Arc<LiveConfig> = self.live_config.borrow().clone();
+        live_config
+            .chain(self.chain_config.chain_id)
+            .cloned()
+            .unwrap_or_else(|| self.chain_config.clone())
+    }
+
+    /// Reads the on-chain status of a batch of orders, `None` marks statuses that could
+    /// not be read.
+    async fn get_onchain_statuses(
+        &self,
+        orderbook: Address,
+        order_ids: &[FixedBytes<32>],
+    ) -> Result<Vec<Option<u8>>> {
+        let multicall_address = self
+            .chain_config
+            .multicall_address
+            .as_deref()
+            .map(str::parse::<Address>)
+            .transpose()?;
+        let statuses = self
+            .rpc_pool
+            .call(|provider| async move {
+                let mut multicall = Multicall::new(&provider);
+                if let Some(multicall_address) = multicall_address {
+                    multicall = multicall.with_address(multicall_address);
+                }
+                let calls = order_ids
+                    .iter()
+                    .map(|order_id| (orderbook, Orderbook::ordersCall { orderId: *order_id }));
+                aggregate(multicall, calls).await
+            })
+            .await?;
+        Ok(statuses
+            .into_iter()
+            .map(|status| status.ok().map(|status| status.status))
+            .collect())
+    }
+
+    async fn reconcile_order(
+        &self,
+        order: &order::Model,
+        onchain_status: Option<u8>,
+    ) -> Result<()> {
+        let order_id = hex::encode(&order.order_id);
+        match drift(&order.order_status, onchain_status) {
+            Drift::None => {}
+            Drift::Unreadable => warn!(order_id, "Unable to read on-chain order status"),
+            Drift::Status(status) => {
+                let drift_total = self.drift_total.fetch_add(1, Ordering::Relaxed) + 1;
+                warn!(
+                    order_id,
+                    indexed_status = ?order.order_status,
+                    onchain_status = ?status,
+                    drift_total,
+                    "Order status drifted from the orderbook, fixing it"
+                );
+                self.count_drift("status");
+                self.order_repository
+                    .update_order_status(order.order_id.clone(), status)
+                    .await?;
+            }
+            // Only reported, as this can also be an order that is being reorged out
+            Drift::Unknown(onchain_status) => {
+                let drift_total = self.drift_total.fetch_add(1, Ordering::Relaxed) + 1;
+                warn!(
+                    order_id,
+                    indexed_status = ?order.order_status,
+                    onchain_status,
+                    drift_total,
+                    "Indexed order is unknown to the orderbook"
+                );
+                self.count_drift("unknown");
+            }
+        }
+        Ok(())
+    }
+
+    fn count_drift(&self, kind: &str) {
+        metrics()
+            .reconciler_drift
+            .with_label_values(&[&self.chain_config.name, kind])
+            .inc();
+    }
+
+    async fn reconcile(&self) -> Result<()> {
+        let pending_orders = self
+            .order_repository
+            .get_pending_orders(self.chain_config.chain_id)
+            .await?;
+        if pending_orders.is_empty() {
+            return Ok(());
+        }
+
+        let orderbook: Address = self.chain_config.order_contract_address.parse()?;
+        let batch_size = self
+            .current_chain_config()
+            .reconciler
+            .batch_size
+            .unwrap_or(DEFAULT_BATCH_SIZE)
+            .max(1);
+
+        for batch in pending_orders.chunks(batch_size) {
+            let order_ids: Vec<FixedBytes<32>> = batch
+                .iter()
+                .map(|order| FixedBytes::from_slice(&order.order_id))
+                .collect();
+            let statuses = self.get_onchain_statuses(orderbook, &order_ids).await?;
+            for (order, status) in batch.iter().zip(statuses) {
+                self.reconcile_order(order, status).await?;
+            }
+        }
+
+        info!(
+            chain_name = self.chain_config.name,
+            orders = pending_orders.len(),
+            drift_total = self.drift_total.load(Ordering::Relaxed),
+            "Reconciled pending orders"
+        );
+        Ok(())
+    }
+}
+
+impl Service for Reconciler {
+    async fn _run(&self) -> Result<()> {
+        loop {
+            // Picks up the configuration reloaded since the last pass
+            let interval = self
+                .current_chain_config()
+                .reconciler
+                .interval
+                .unwrap_or(DEFAULT_RECONCILE_INTERVAL);
+            tokio::time::sleep(tokio::time::Duration::from_millis(interval)).await;
+
+            match self.reconcile().await {
+                Ok(()) => self.report_success(),
+                Err(e) => {
+                    warn!(
+                        chain_name = self.chain_config.name,
+                        error = %e,
+                        "Unable to reconcile order statuses"
+                    );
+                    self.report_error(&e);
+                }
+            }
+        }
+    }
+
+    fn service_name(&self) -> String {
+        format!("{} Reconciler", self.chain_config.name)
+    }
+
+    fn chain_config(&self) -> &ChainConfig {
+        &self.chain_config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use entity::sea_orm_active_enums::OrderStatus;
+
+    use super::{drift, Drift};
+
+    #[test]
+    fn test_drift() {
+        // Filled on-chain while still pending in the index
+        assert_eq!(
+            drift(&OrderStatus::Created, Some(2)),
+            Drift::Status(OrderStatus::Filled)
+        );
+        assert_eq!(
+            drift(&OrderStatus::Created, Some(3)),
+            Drift::Status(OrderStatus::Withdrawn)
+        );
+        // Missing from the orderbook
+        assert_eq!(drift(&OrderStatus::Created, Some(0)), Drift::Unknown(0));
+        assert_eq!(drift(&OrderStatus::Created, Some(1)), Drift::None);
+        assert_eq!(drift(&OrderStatus::Created, None), Drift::Unreadable);
+    }
+}
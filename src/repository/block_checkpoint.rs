@@ -1,6 +1,8 @@
-use ::entity::block_checkpoint::{self, ActiveModel, Entity as LastProcessedBlock};
+use ::entity::block_checkpoint::{self, ActiveModel, Column, Entity as LastProcessedBlock};
 use eyre::{Ok, Result};
-use sea_orm::*;
+use sea_orm::{sea_query::OnConflict, *};
+
+use crate::metrics::instrument_connection;
 
 pub struct BlockCheckpointRepository {
     pub connection: sea_orm::DatabaseConnection,
@@ -8,25 +10,44 @@ pub struct BlockCheckpointRepository {
 
 impl BlockCheckpointRepository {
     pub async fn new(postgres_url: String) -> Result<Self> {
-        let connection: sea_orm::DatabaseConnection = Database::connect(postgres_url).await?;
+        let mut connection: sea_orm::DatabaseConnection = Database::connect(postgres_url).await?;
+        instrument_connection(&mut connection);
         Ok(Self { connection })
     }
 
-    pub async fn get_last_block_checkpoint(&self) -> Result<Option<block_checkpoint::Model>> {
+    pub async fn get_last_block_checkpoint(
+        &self,
+        chain_id: u64,
+    ) -> Result<Option<block_checkpoint::Model>> {
         Ok(LastProcessedBlock::find()
-            .order_by_desc(block_checkpoint::Column::Height)
+            .filter(Column::ChainId.eq(chain_id))
             .one(&self.connection)
             .await?)
     }
 
-    pub async fn create_block_checkpoint(&self, chain_id: u64, height: u64) -> Result<()> {
+    /// Checkpoints of all the chains.
+    pub async fn get_block_checkpoints(&self) -> Result<Vec<block_checkpoint::Model>> {
+        Ok(LastProcessedBlock::find()
+            .order_by_asc(Column::ChainId)
+            .all(&self.connection)
+            .await?)
+    }
+
+    /// Moves the chain's checkpoint, there is a single row per chain.
+    pub async fn set_block_checkpoint(&self, chain_id: u64, height: u64) -> Result<()> {
         let block = ActiveModel {
             chain_id: Set(chain_id as i64),
             height: Set(height as i64),
+            processed_at: Set(chrono::Utc::now().naive_utc()),
             ..Default::default()
         };
-        block_checkpoint::Entity::insert(block)
-            .exec(&self.connection)
+        LastProcessedBlock::insert(block)
+            .on_conflict(
+                OnConflict::column(Column::ChainId)
+                    .update_columns([Column::Height, Column::ProcessedAt])
+                    .to_owned(),
+            )
+            .exec_without_returning(&self.connection)
             .await?;
         Ok(())
     }
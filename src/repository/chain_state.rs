@@ -0,0 +1,130 @@
+use ::entity::chain_state::{self, ActiveModel, Column, Entity as ChainState};
+use eyre::{Ok, Result};
+use sea_orm::{
+    sea_query::{Expr, OnConflict},
+    *,
+};
+
+use crate::metrics::instrument_connection;
+
+pub struct ChainStateRepository {
+    pub connection: sea_orm::DatabaseConnection,
+}
+
+impl ChainStateRepository {
+    pub async fn new(postgres_url: String) -> Result<Self> {
+        let mut connection: sea_orm::DatabaseConnection = Database::connect(postgres_url).await?;
+        instrument_connection(&mut connection);
+        Ok(Self { connection })
+    }
+
+    pub async fn get_chain_state(&self, chain_id: u64) -> Result<Option<chain_state::Model>> {
+        Ok(ChainState::find()
+            .filter(Column::ChainId.eq(chain_id))
+            .one(&self.connection)
+            .await?)
+    }
+
+    /// Stores the `eth_getLogs` range size learned for the chain.
+    pub async fn set_log_range_size(&self, chain_id: u64, log_range_size: u64) -> Result<()> {
+        let state = ActiveModel {
+            chain_id: Set(chain_id as i64),
+            log_range_size: Set(Some(log_range_size as i64)),
+            updated_at: Set(chrono::Utc::now().naive_utc()),
+            ..Default::default()
+        };
+        self.upsert(state, vec![Column::LogRangeSize]).await
+    }
+
+    /// Upserts the chain's row, only updating the given columns.
+    async fn upsert(&self, state: ActiveModel, columns: Vec<Column>) -> Result<()> {
+        ChainState::insert(state)
+            .on_conflict(
+                OnConflict::column(Column::ChainId)
+                    .update_columns(columns.into_iter().chain([Column::UpdatedAt]))
+                    .to_owned(),
+            )
+            .exec_without_returning(&self.connection)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_filler_paused(&self, chain_id: u64, paused: bool) -> Result<()> {
+        let state = ActiveModel {
+            chain_id: Set(chain_id as i64),
+            filler_paused: Set(paused),
+            updated_at: Set(chrono::Utc::now().naive_utc()),
+            ..Default::default()
+        };
+        self.upsert(state, vec![Column::FillerPaused]).await
+    }
+
+    pub async fn set_listener_paused(&self, chain_id: u64, paused: bool) -> Result<()> {
+        let state = ActiveModel {
+            chain_id: Set(chain_id as i64),
+            listener_paused: Set(paused),
+            updated_at: Set(chrono::Utc::now().naive_utc()),
+            ..Default::default()
+        };
+        self.upsert(state, vec![Column::ListenerPaused]).await
+    }
+
+    /// Asks the chain's listener to index `from_block..=to_block` again, replacing any pending
+    /// request. Without `to_block` the listener moves its cursor back to `from_block`.
+    pub async fn request_reindex(
+        &self,
+        chain_id: u64,
+        from_block: u64,
+        to_block: Option<u64>,
+    ) -> Result<()> {
+        let state = ActiveModel {
+            chain_id: Set(chain_id as i64),
+            reindex_from_block: Set(Some(from_block as i64)),
+            reindex_to_block: Set(to_block.map(|to_block| to_block as i64)),
+            updated_at: Set(chrono::Utc::now().naive_utc()),
+            ..Default::default()
+        };
+        self.upsert(
+            state,
+            vec![Column::ReindexFromBlock, Column::ReindexToBlock],
+        )
+        .await
+    }
+
+    /// Pending re-index request of the chain, left in place until it is cleared.
+    pub async fn get_reindex_request(&self, chain_id: u64) -> Result<Option<(u64, Option<u64>)>> {
+        Ok(self.get_chain_state(chain_id).await?.and_then(|state| {
+            let from_block = state.reindex_from_block?;
+            Some((
+                from_block as u64,
+                state.reindex_to_block.map(|to_block| to_block as u64),
+            ))
+        }))
+    }
+
+    /// Clears a completed re-index request, unless an operator replaced it in the meantime.
+    pub async fn clear_reindex_request(
+        &self,
+        chain_id: u64,
+        from_block: u64,
+        to_block: Option<u64>,
+    ) -> Result<()> {
+        let to_block_condition = match to_block {
+            Some(to_block) => Column::ReindexToBlock.eq(to_block as i64),
+            None => Column::ReindexToBlock.is_null(),
+        };
+        ChainState::update_many()
+            .col_expr(Column::ReindexFromBlock, Expr::value(Option::<i64>::None))
+            .col_expr(Column::ReindexToBlock, Expr::value(Option::<i64>::None))
+            .col_expr(
+                Column::UpdatedAt,
+                Expr::value(chrono::Utc::now().naive_utc()),
+            )
+            .filter(Column::ChainId.eq(chain_id as i64))
+            .filter(Column::ReindexFromBlock.eq(from_block as i64))
+            .filter(to_block_condition)
+            .exec(&self.connection)
+            .await?;
+        Ok(())
+    }
+}
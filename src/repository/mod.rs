@@ -1,2 +1,7 @@
 pub mod block_checkpoint;
+pub mod chain_state;
+pub mod contract_config;
 pub mod order;
+pub mod order_event;
+pub mod order_settlement;
+pub mod spending_counter;
@@ -1,9 +1,29 @@
 use ::entity::{
     order::{self, ActiveModel, Entity as Order},
+    order_token::{self, Entity as OrderToken},
+    pending_fill::{self, Entity as PendingFill},
     sea_orm_active_enums::OrderStatus,
 };
 use eyre::{Ok, Result};
-use sea_orm::*;
+use sea_orm::{
+    sea_query::{Expr, Query},
+    *,
+};
+
+use crate::metrics::instrument_connection;
+
+/// Filters of the orders API, addresses in their persisted `_bytes64` form.
+#[derive(Debug, Default)]
+pub struct OrderFilter {
+    pub chain_id: Option<u64>,
+    pub status: Option<OrderStatus>,
+    pub user: Option<Vec<u8>>,
+    pub filler: Option<Vec<u8>>,
+    pub deadline_after: Option<chrono::NaiveDateTime>,
+    pub deadline_before: Option<chrono::NaiveDateTime>,
+    /// Orders with this token in their inputs or outputs.
+    pub token: Option<Vec<u8>>,
+}
 
 pub struct OrderRepository {
     pub connection: sea_orm::DatabaseConnection,
@@ -11,10 +31,18 @@ pub struct OrderRepository {
 
 impl OrderRepository {
     pub async fn new(postgres_url: String) -> Result<Self> {
-        let connection: sea_orm::DatabaseConnection = Database::connect(postgres_url).await?;
+        let mut connection: sea_orm::DatabaseConnection = Database::connect(postgres_url).await?;
+        instrument_connection(&mut connection);
         Ok(OrderRepository { connection })
     }
 
+    pub async fn find_order(&self, order_id: Vec<u8>) -> Result<Option<order::Model>> {
+        Ok(Order::find()
+            .filter(order::Column::OrderId.eq(order_id))
+            .one(&self.connection)
+            .await?)
+    }
+
     pub async fn get_order(&self, order_id: Vec<u8>) -> Result<order::Model> {
         let order = Order::find()
             .filter(order::Column::OrderId.eq(order_id))
@@ -23,6 +51,7 @@ impl OrderRepository {
         order.ok_or(eyre::eyre!("Order not found"))
     }
 
+    #[allow(dead_code)]
     pub async fn create_order(&self, order: &ActiveModel) -> Result<()> {
         order::Entity::insert(order.clone())
             .exec(&self.connection)
@@ -30,6 +59,94 @@ impl OrderRepository {
         Ok(())
     }
 
+    pub async fn create_order_with_tokens(
+        &self,
+        order: &ActiveModel,
+        tokens: Vec<order_token::ActiveModel>,
+    ) -> Result<()> {
+        let txn = self.connection.begin().await?;
+        order::Entity::insert(order.clone()).exec(&txn).await?;
+        if !tokens.is_empty() {
+            OrderToken::insert_many(tokens).exec(&txn).await?;
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_order_tokens(&self, order_id: Vec<u8>) -> Result<Vec<order_token::Model>> {
+        let tokens = OrderToken::find()
+            .filter(order_token::Column::OrderId.eq(order_id))
+            .order_by_asc(order_token::Column::Side)
+            .order_by_asc(order_token::Column::Position)
+            .all(&self.connection)
+            .await?;
+        Ok(tokens)
+    }
+
+    /// Token legs of several orders at once.
+    pub async fn get_tokens_of_orders(
+        &self,
+        order_ids: Vec<Vec<u8>>,
+    ) -> Result<Vec<order_token::Model>> {
+        let tokens = OrderToken::find()
+            .filter(order_token::Column::OrderId.is_in(order_ids))
+            .order_by_asc(order_token::Column::Side)
+            .order_by_asc(order_token::Column::Position)
+            .all(&self.connection)
+            .await?;
+        Ok(tokens)
+    }
+
+    /// Page of orders matching the filter, newest first. Pages are chained by passing the id
+    /// of the last order of the previous page as `before_id`.
+    pub async fn list_orders(
+        &self,
+        filter: &OrderFilter,
+        before_id: Option<i32>,
+        limit: u64,
+    ) -> Result<Vec<order::Model>> {
+        let mut query = Order::find()
+            .apply_if(filter.chain_id, |query, chain_id| {
+                query.filter(order::Column::ChainId.eq(chain_id))
+            })
+            .apply_if(filter.status.clone(), |query, status| {
+                query.filter(order::Column::OrderStatus.eq(status))
+            })
+            .apply_if(filter.user.clone(), |query, user| {
+                query.filter(order::Column::User.eq(user))
+            })
+            .apply_if(filter.filler.clone(), |query, filler| {
+                query.filter(order::Column::Filler.eq(filler))
+            })
+            .apply_if(filter.deadline_after, |query, deadline| {
+                query.filter(order::Column::Deadline.gte(deadline))
+            })
+            .apply_if(filter.deadline_before, |query, deadline| {
+                query.filter(order::Column::Deadline.lt(deadline))
+            })
+            .apply_if(before_id, |query, id| {
+                query.filter(order::Column::Id.lt(id))
+            });
+        if let Some(token) = &filter.token {
+            query = query.filter(
+                order::Column::OrderId.in_subquery(
+                    Query::select()
+                        .column(order_token::Column::OrderId)
+                        .from(OrderToken)
+                        .and_where(order_token::Column::TokenAddress.eq(token.clone()))
+                        .to_owned(),
+                ),
+            );
+        }
+
+        let orders = query
+            .order_by_desc(order::Column::Id)
+            .limit(limit)
+            .all(&self.connection)
+            .await?;
+        Ok(orders)
+    }
+
     #[allow(dead_code)]
     pub async fn delete_order(&self, order_id: Vec<u8>) -> Result<()> {
         order::Entity::delete_many()
@@ -57,12 +174,57 @@ impl OrderRepository {
         Ok(())
     }
 
+    pub async fn reject_order(&self, order_id: Vec<u8>, rejection_reason: &str) -> Result<()> {
+        Order::update_many()
+            .col_expr(
+                order::Column::RejectionReason,
+                Expr::value(rejection_reason.to_string()),
+            )
+            .filter(order::Column::OrderId.eq(order_id))
+            .exec(&self.connection)
+            .await?;
+        Ok(())
+    }
+
+    /// Lets the filler pick up a rejected order again.
+    pub async fn clear_rejection(&self, order_id: Vec<u8>) -> Result<()> {
+        Order::update_many()
+            .col_expr(
+                order::Column::RejectionReason,
+                Expr::value(Option::<String>::None),
+            )
+            .filter(order::Column::OrderId.eq(order_id))
+            .exec(&self.connection)
+            .await?;
+        Ok(())
+    }
+
+    /// Orders of a chain that have not reached a terminal status yet.
+    pub async fn get_pending_orders(&self, chain_id: u64) -> Result<Vec<order::Model>> {
+        let pending_orders = Order::find()
+            .filter(order::Column::ChainId.eq(chain_id))
+            .filter(order::Column::OrderStatus.eq(OrderStatus::Created))
+            .all(&self.connection)
+            .await?;
+        Ok(pending_orders)
+    }
+
     pub async fn get_ready_orders(&self, chain_id: u64) -> Result<Vec<order::Model>> {
         let ready_orders = Order::find()
             .filter(order::Column::PrimaryFillerDeadline.gt(chrono::Utc::now().naive_utc()))
             .filter(order::Column::Deadline.gt(chrono::Utc::now().naive_utc()))
             .filter(order::Column::ChainId.eq(chain_id))
             .filter(order::Column::OrderStatus.eq(OrderStatus::Created))
+            .filter(order::Column::RejectionReason.is_null())
+            // Orders with a fill in flight are settled from its receipt
+            .filter(
+                order::Column::OrderId.not_in_subquery(
+                    Query::select()
+                        .column(pending_fill::Column::OrderId)
+                        .from(PendingFill)
+                        .to_owned(),
+                ),
+            )
             .all(&self.connection)
             .await?;
         Ok(ready_orders)
@@ -97,6 +259,7 @@ mod tests {
             call_recipient: ActiveValue::NotSet,
             call_data: ActiveValue::NotSet,
             order_status: ActiveValue::Set(OrderStatus::Created),
+            rejection_reason: ActiveValue::NotSet,
         };
 
         let order_repository = OrderRepository::new(postgres_url).await?;
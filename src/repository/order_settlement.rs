@@ -0,0 +1,37 @@
+use ::entity::order_settlement::{self, ActiveModel, Column, Entity as OrderSettlement};
+use eyre::{Ok, Result};
+use sea_orm::{sea_query::OnConflict, *};
+
+use crate::metrics::instrument_connection;
+
+pub struct OrderSettlementRepository {
+    pub connection: sea_orm::DatabaseConnection,
+}
+
+impl OrderSettlementRepository {
+    pub async fn new(postgres_url: String) -> Result<Self> {
+        let mut connection: sea_orm::DatabaseConnection = Database::connect(postgres_url).await?;
+        instrument_connection(&mut connection);
+        Ok(Self { connection })
+    }
+
+    /// Records a settlement, an order is only settled once so replays are ignored.
+    pub async fn create_settlement(&self, settlement: ActiveModel) -> Result<()> {
+        OrderSettlement::insert(settlement)
+            .on_conflict(OnConflict::column(Column::OrderId).do_nothing().to_owned())
+            .exec_without_returning(&self.connection)
+            .await?;
+        Ok(())
+    }
+
+    #[allow(dead_code)]
+    pub async fn get_settlement(
+        &self,
+        order_id: Vec<u8>,
+    ) -> Result<Option<order_settlement::Model>> {
+        Ok(OrderSettlement::find()
+            .filter(Column::OrderId.eq(order_id))
+            .one(&self.connection)
+            .await?)
+    }
+}
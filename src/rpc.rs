@@ -0,0 +1,503 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use alloy::{
+    providers::{Provider, ProviderBuilder, RootProvider},
+    transports::{BoxTransport, TransportError, TransportErrorKind},
+};
+use futures_util::future::join_all;
+use tokio::sync::OnceCell;
+use tracing::{debug, warn};
+
+use crate::context::{redact_url, ChainConfig, RpcEndpointConfig, RpcStrategy};
+
+const MAX_COOLDOWN: Duration = Duration::from_secs(60);
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(1);
+/// Longest a request waits for a rate limited or cooling down endpoint to free up.
+const MAX_WAIT: Duration = Duration::from_secs(5);
+
+pub type HttpProvider = RootProvider<BoxTransport>;
+
+#[derive(Debug, Default)]
+struct EndpointHealth {
+    consecutive_failures: u32,
+    cooldown_until: Option<Instant>,
+    latest_block: Option<u64>,
+    behind: bool,
+}
+
+impl EndpointHealth {
+    fn is_available(&self, now: Instant) -> bool {
+        !self.behind && self.cooldown_until.is_none_or(|until| until <= now)
+    }
+}
+
+#[derive(Debug)]
+struct RateLimit {
+    window_start: Instant,
+    requests: u32,
+}
+
+struct Endpoint {
+    config: RpcEndpointConfig,
+    provider: OnceCell<HttpProvider>,
+    health: Mutex<EndpointHealth>,
+    rate_limit: Mutex<RateLimit>,
+}
+
+impl Endpoint {
+    async fn provider(&self) -> Result<HttpProvider, TransportError> {
+        self.provider
+            .get_or_try_init(|| ProviderBuilder::new().on_builtin(self.config.http_url.expose()))
+            .await
+            .cloned()
+    }
+
+    /// Takes a request slot on the endpoint. An endpoint that is cooling down or out of
+    /// requests for the current window tells when it frees up instead.
+    fn try_acquire(&self, now: Instant) -> Result<(), Instant> {
+        let cooldown_until = self.health.lock().unwrap().cooldown_until;
+        if let Some(until) = cooldown_until.filter(|until| *until > now) {
+            return Err(until);
+        }
+        let Some(max) = self.config.max_requests_per_second else {
+            return Ok(());
+        };
+        let mut rate_limit = self.rate_limit.lock().unwrap();
+        if now.duration_since(rate_limit.window_start) >= RATE_LIMIT_WINDOW {
+            rate_limit.window_start = now;
+            rate_limit.requests = 0;
+        }
+        if rate_limit.requests >= max {
+            return Err(rate_limit.window_start + RATE_LIMIT_WINDOW);
+        }
+        rate_limit.requests += 1;
+        Ok(())
+    }
+
+    fn record_success(&self) {
+        let mut health = self.health.lock().unwrap();
+        health.consecutive_failures = 0;
+        health.cooldown_until = None;
+    }
+
+    /// Failing endpoints are benched for a cooldown that doubles with every failure in a row.
+    fn record_failure(&self, now: Instant) {
+        let mut health = self.health.lock().unwrap();
+        health.consecutive_failures += 1;
+        let cooldown =
+            Duration::from_secs(1 << health.consecutive_failures.min(6)).min(MAX_COOLDOWN);
+        health.cooldown_until = Some(now + cooldown);
+    }
+}
+
+/// The RPC endpoints of a chain, shared by all the services that talk to it.
+pub struct RpcPool {
+    chain_name: String,
+    endpoints: Vec<Endpoint>,
+    strategy: RpcStrategy,
+    quorum: usize,
+    max_block_lag: Option<u64>,
+    next: AtomicUsize,
+}
+
+impl RpcPool {
+    pub fn new(chain_config: &ChainConfig) -> Self {
+        let now = Instant::now();
+        let endpoints = chain_config
+            .rpc_endpoints()
+            .into_iter()
+            .map(|config| Endpoint {
+                config,
+                provider: OnceCell::new(),
+                health: Mutex::new(EndpointHealth::default()),
+                rate_limit: Mutex::new(RateLimit {
+                    window_start: now,
+                    requests: 0,
+                }),
+            })
+            .collect();
+        Self {
+            chain_name: chain_config.name.clone(),
+            endpoints,
+            strategy: chain_config.rpc.strategy,
+            quorum: chain_config.rpc.quorum.unwrap_or(1).max(1),
+            max_block_lag: chain_config.rpc.max_block_lag,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Endpoint indexes in the order they should be tried. Unavailable endpoints come last,
+    /// lagging ones are still used when nothing else is left.
+    fn candidates(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let mut candidates: Vec<usize> = (0..self.endpoints.len()).collect();
+        if self.strategy == RpcStrategy::RoundRobin && !candidates.is_empty() {
+            let start = self.next.fetch_add(1, Ordering::Relaxed) % candidates.len();
+            candidates.rotate_left(start);
+        }
+        candidates.sort_by_key(|&i| {
+            let endpoint = &self.endpoints[i];
+            let health = endpoint.health.lock().unwrap();
+            let priority = match self.strategy {
+                RpcStrategy::Priority => endpoint.config.priority,
+                RpcStrategy::RoundRobin => 0,
+            };
+            (
+                !health.is_available(now),
+                health.consecutive_failures,
+                priority,
+            )
+        });
+        candidates
+    }
+
+    /// Runs a request against the best endpoint, failing over to the next ones when the
+    /// endpoint cannot be reached. Errors returned by the node itself are not retried. When
+    /// every endpoint is rate limited or cooling down, waits for the first one to free up.
+    pub async fn call<T, F, Fut>(&self, request: F) -> Result<T, TransportError>
+    where
+        F: Fn(HttpProvider) -> Fut,
+        Fut: Future<Output = Result<T, TransportError>>,
+    {
+        let deadline = Instant::now() + MAX_WAIT;
+        loop {
+            let mut last_error = None;
+            let mut free_at: Option<Instant> = None;
+            for i in self.candidates() {
+                let endpoint = &self.endpoints[i];
+                if let Err(at) = endpoint.try_acquire(Instant::now()) {
+                    debug!(
+                        chain_name = self.chain_name,
+                        url = redact_url(endpoint.config.http_url.expose()),
+                        "Endpoint rate limited or cooling down, trying the next one"
+                    );
+                    free_at = Some(free_at.map_or(at, |free_at| free_at.min(at)));
+                    continue;
+                }
+
+                let result = match endpoint.provider().await {
+                    Ok(provider) => request(provider).await,
+                    Err(e) => Err(e),
+                };
+                match result {
+                    Ok(value) => {
+                        endpoint.record_success();
+                        return Ok(value);
+                    }
+                    Err(e) if e.as_error_resp().is_some() => {
+                        endpoint.record_success();
+                        return Err(e);
+                    }
+                    Err(e) => {
+                        warn!(
+                            chain_name = self.chain_name,
+                            url = redact_url(endpoint.config.http_url.expose()),
+                            error = %e,
+                            "RPC endpoint failed, failing over"
+                        );
+                        endpoint.record_failure(Instant::now());
+                        last_error = Some(e);
+                    }
+                }
+            }
+
+            if let Some(e) = last_error {
+                return Err(e);
+            }
+            match free_at {
+                Some(free_at) if free_at <= deadline => {
+                    debug!(
+                        chain_name = self.chain_name,
+                        "Every endpoint is busy, waiting for one to free up"
+                    );
+                    tokio::time::sleep_until(free_at.into()).await;
+                }
+                _ => {
+                    return Err(TransportErrorKind::custom_str(&format!(
+                        "No RPC endpoint available for {}",
+                        self.chain_name
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Provider of the best endpoint, for work that does not fit in a single request.
+    pub async fn provider(&self) -> Result<HttpProvider, TransportError> {
+        let i = self.candidates().first().copied().ok_or_else(|| {
+            TransportErrorKind::custom_str(&format!(
+                "No RPC endpoint configured for {}",
+                self.chain_name
+            ))
+        })?;
+        self.endpoints[i].provider().await
+    }
+
+    /// HTTP url of the best endpoint, for clients the pool does not build itself.
+    pub fn http_url(&self) -> Option<String> {
+        self.candidates()
+            .first()
+            .map(|&i| self.endpoints[i].config.http_url.expose().to_string())
+    }
+
+    /// WebSocket url of the best endpoint that has one.
+    pub fn ws_url(&self) -> Option<String> {
+        self.candidates().into_iter().find_map(|i| {
+            let ws_url = self.endpoints[i].config.ws_url.as_ref()?;
+            Some(ws_url.expose().to_string())
+        })
+    }
+
+    /// Reports a failure on a connection made outside of the pool, e.g. a subscription.
+    pub fn record_failure(&self, url: &str) {
+        let now = Instant::now();
+        self.endpoints
+            .iter()
+            .filter(|endpoint| {
+                endpoint.config.http_url.expose() == url
+                    || endpoint
+                        .config
+                        .ws_url
+                        .as_ref()
+                        .is_some_and(|ws_url| ws_url.expose() == url)
+            })
+            .for_each(|endpoint| endpoint.record_failure(now));
+    }
+
+    /// Asks every endpoint for its latest block, takes those that fell behind out of rotation
+    /// and returns the highest block reached by at least `quorum` endpoints. Endpoints that
+    /// are rate limited or cooling down are not asked.
+    pub async fn latest_block(&self) -> Result<u64, TransportError> {
+        let now = Instant::now();
+        let endpoints = self
+            .endpoints
+            .iter()
+            .filter(|endpoint| endpoint.try_acquire(now).is_ok())
+            .collect::<Vec<_>>();
+        if endpoints.is_empty() {
+            return Err(TransportErrorKind::custom_str(&format!(
+                "No RPC endpoint available for {}",
+                self.chain_name
+            )));
+        }
+        let blocks = join_all(endpoints.iter().map(|endpoint| async move {
+            let provider = endpoint.provider().await?;
+            provider.get_block_number().await
+        }))
+        .await;
+
+        let now = Instant::now();
+        let mut reached = vec![];
+        for (endpoint, block) in endpoints.iter().zip(&blocks) {
+            match block {
+                Ok(block) => {
+                    endpoint.record_success();
+                    endpoint.health.lock().unwrap().latest_block = Some(*block);
+                    reached.push(*block);
+                }
+                Err(e) => {
+                    warn!(
+                        chain_name = self.chain_name,
+                        url = redact_url(endpoint.config.http_url.expose()),
+                        error = %e,
+                        "Unable to get the latest block"
+                    );
+                    endpoint.record_failure(now);
+                }
+            }
+        }
+
+        let Some(head) = reached.iter().max().copied() else {
+            return Err(blocks
+                .into_iter()
+                .find_map(Result::err)
+                .unwrap_or_else(|| TransportErrorKind::custom_str("No RPC endpoint configured")));
+        };
+        for endpoint in &self.endpoints {
+            let mut health = endpoint.health.lock().unwrap();
+            let behind = match (self.max_block_lag, health.latest_block) {
+                (Some(max_block_lag), Some(latest_block)) => {
+                    head.saturating_sub(latest_block) > max_block_lag
+                }
+                _ => false,
+            };
+            if behind && !health.behind {
+                warn!(
+                    chain_name = self.chain_name,
+                    url = redact_url(endpoint.config.http_url.expose()),
+                    latest_block = health.latest_block,
+                    head,
+                    "RPC endpoint fell behind the head"
+                );
+            }
+            health.behind = behind;
+        }
+
+        quorum_block(reached, self.quorum).ok_or_else(|| {
+            TransportErrorKind::custom_str(&format!(
+                "Latest block quorum of {} not reached for {}",
+                self.quorum, self.chain_name
+            ))
+        })
+    }
+}
+
+/// Highest block that at least `quorum` of the reported blocks have reached.
+fn quorum_block(mut blocks: Vec<u64>, quorum: usize) -> Option<u64> {
+    blocks.sort_unstable_by(|a, b| b.cmp(a));
+    blocks.get(quorum.max(1) - 1).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use alloy::{
+        node_bindings::Anvil,
+        primitives::U256,
+        providers::{ext::AnvilApi, Provider},
+    };
+
+    use super::{quorum_block, RpcPool};
+    use crate::context::{ChainConfig, RpcConfig, RpcEndpointConfig, RpcStrategy};
+
+    fn endpoint(http_url: &str, priority: u32) -> RpcEndpointConfig {
+        RpcEndpointConfig {
+            http_url: http_url.into(),
+            priority,
+            ..Default::default()
+        }
+    }
+
+    fn chain(endpoints: Vec<RpcEndpointConfig>, strategy: RpcStrategy) -> ChainConfig {
+        ChainConfig {
+            name: "test".to_string(),
+            rpc: RpcConfig {
+                endpoints,
+                strategy,
+                quorum: Some(2),
+                max_block_lag: Some(5),
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_quorum_block() {
+        assert_eq!(quorum_block(vec![10, 12, 11], 1), Some(12));
+        assert_eq!(quorum_block(vec![10, 12, 11], 2), Some(11));
+        assert_eq!(quorum_block(vec![10, 12, 11], 3), Some(10));
+        assert_eq!(quorum_block(vec![10, 12], 3), None);
+        assert_eq!(quorum_block(vec![], 1), None);
+    }
+
+    #[test]
+    fn test_candidates() {
+        let pool = RpcPool::new(&chain(
+            vec![endpoint("http://a", 1), endpoint("http://b", 0)],
+            RpcStrategy::Priority,
+        ));
+        assert_eq!(pool.candidates(), vec![1, 0]);
+        assert_eq!(pool.http_url().as_deref(), Some("http://b"));
+
+        pool.record_failure("http://b");
+        assert_eq!(pool.candidates(), vec![0, 1]);
+        pool.endpoints[1].record_success();
+        assert_eq!(pool.candidates(), vec![1, 0]);
+
+        let pool = RpcPool::new(&chain(
+            vec![endpoint("http://a", 0), endpoint("http://b", 0)],
+            RpcStrategy::RoundRobin,
+        ));
+        let first = pool.candidates();
+        let second = pool.candidates();
+        assert_ne!(first[0], second[0]);
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let mut config = endpoint("http://a", 0);
+        config.max_requests_per_second = Some(2);
+        let pool = RpcPool::new(&chain(vec![config], RpcStrategy::Priority));
+
+        let now = Instant::now();
+        assert!(pool.endpoints[0].try_acquire(now).is_ok());
+        assert!(pool.endpoints[0].try_acquire(now).is_ok());
+        assert!(pool.endpoints[0]
+            .try_acquire(now)
+            .is_err_and(|free_at| free_at <= now + super::RATE_LIMIT_WINDOW));
+        assert!(pool.endpoints[0]
+            .try_acquire(now + super::RATE_LIMIT_WINDOW)
+            .is_ok());
+
+        pool.endpoints[0].record_failure(now);
+        assert_eq!(
+            pool.endpoints[0].try_acquire(now),
+            Err(now + Duration::from_secs(2))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_call_waits_for_a_free_endpoint() {
+        let mut config = endpoint("http://a", 0);
+        config.max_requests_per_second = Some(1);
+        let pool = RpcPool::new(&chain(vec![config], RpcStrategy::Priority));
+
+        let start = Instant::now();
+        for _ in 0..2 {
+            pool.call(|_| async { Ok(()) }).await.unwrap();
+        }
+        assert!(start.elapsed() >= super::RATE_LIMIT_WINDOW - Duration::from_millis(50));
+
+        // Cooldowns longer than the wait are not waited for
+        for _ in 0..3 {
+            pool.endpoints[0].record_failure(Instant::now());
+        }
+        let start = Instant::now();
+        assert!(pool.call(|_| async { Ok(()) }).await.is_err());
+        assert!(start.elapsed() < super::MAX_WAIT);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_failover_and_lag() -> eyre::Result<()> {
+        let first = Anvil::new().try_spawn()?;
+        let second = Anvil::new().try_spawn()?;
+        let third = Anvil::new().try_spawn()?;
+        let pool = RpcPool::new(&chain(
+            vec![
+                endpoint(&first.endpoint(), 0),
+                endpoint(&second.endpoint(), 1),
+                endpoint(&third.endpoint(), 2),
+            ],
+            RpcStrategy::Priority,
+        ));
+
+        // The first endpoint gets ahead, the third one falls more than 5 blocks behind
+        let provider = pool.endpoints[0].provider().await?;
+        provider.anvil_mine(Some(U256::from(10)), None).await?;
+        pool.endpoints[1]
+            .provider()
+            .await?
+            .anvil_mine(Some(U256::from(8)), None)
+            .await?;
+        assert_eq!(pool.latest_block().await?, 8);
+        assert!(pool.endpoints[2].health.lock().unwrap().behind);
+
+        // Requests fail over once the first endpoint goes away
+        drop(first);
+        let block = pool
+            .call(|provider| async move { provider.get_block_number().await })
+            .await?;
+        assert_eq!(block, 8);
+        assert_eq!(pool.http_url(), Some(second.endpoint()));
+        Ok(())
+    }
+}
@@ -0,0 +1,394 @@
+use std::{collections::HashMap, path::Path, sync::Arc, time::Duration};
+
+use alloy::primitives::Address;
+use eyre::Result;
+use notify::{EventKind, RecursiveMode, Watcher};
+use tokio::{
+    signal::unix::{signal, SignalKind},
+    sync::{mpsc, watch},
+    task::{AbortHandle, JoinSet},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+use crate::{
+    alert,
+    cli::{find_chain, ServiceKind},
+    context::{self, AppConfig, ChainConfig},
+    filler::Filler,
+    health::health,
+    listener, reconciler,
+    rpc::RpcPool,
+    server,
+    service::Service,
+    token::{fetch_token_metadata, TokenRegistry},
+};
+
+const CONFIG_FILE: &str = "config.toml";
+
+/// Time given to editors to finish writing the configuration before it is read.
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Configuration the running services read on each loop, replaced as a whole on reload.
+pub struct LiveConfig {
+    pub chains: Vec<ChainConfig>,
+    pub rpc_pools: HashMap<u64, Arc<RpcPool>>,
+    pub token_registry: TokenRegistry,
+    pub filler_addresses: Vec<Address>,
+}
+
+pub type LiveConfigReceiver = watch::Receiver<Arc<LiveConfig>>;
+
+impl LiveConfig {
+    /// Builds the live configuration, keeping the RPC pools of the chains that were already
+    /// running so their endpoint health survives a reload.
+    async fn new(config: &AppConfig, previous: Option<&LiveConfig>) -> Result<Self> {
+        let rpc_pools: HashMap<u64, Arc<RpcPool>> = config
+            .chain
+            .iter()
+            .map(|chain| {
+                let rpc_pool = previous
+                    .and_then(|previous| previous.rpc_pools.get(&chain.chain_id).cloned())
+                    .unwrap_or_else(|| Arc::new(RpcPool::new(chain)));
+                (chain.chain_id, rpc_pool)
+            })
+            .collect();
+
+        let mut chains = config.chain.clone();
+        for chain in &mut chains {
+            if let Err(e) = fetch_token_metadata(chain, &rpc_pools[&chain.chain_id]).await {
+                warn!(chain_name = chain.name, error = %e, "Unable to read token metadata");
+            }
+        }
+
+        Ok(Self {
+            token_registry: TokenRegistry::new(&chains),
+            filler_addresses: config.filler_addresses()?,
+            chains,
+            rpc_pools,
+        })
+    }
+
+    pub fn chain(&self, chain_id: u64) -> Option<&ChainConfig> {
+        self.chains.iter().find(|chain| chain.chain_id == chain_id)
+    }
+}
+
+/// Fields that only take effect on restart and differ between the two configurations.
+fn fixed_changes(old: &AppConfig, new: &AppConfig) -> Vec<String> {
+    let mut changes = vec![];
+    let mut check = |field: &str, changed: bool| {
+        if changed {
+            changes.push(field.to_string());
+        }
+    };
+    check("postgres_url", old.postgres_url != new.postgres_url);
+    check("auto_migrate", old.auto_migrate != new.auto_migrate);
+    check("server", old.server != new.server);
+    check("log", old.log != new.log);
+    check("alerts", old.alerts != new.alerts);
+
+    for old_chain in &old.chain {
+        let Some(new_chain) = new
+            .chain
+            .iter()
+            .find(|chain| chain.chain_id == old_chain.chain_id)
+        else {
+            continue;
+        };
+        let mut check = |field: &str, changed: bool| {
+            check(&format!("chain {}: {field}", old_chain.name), changed)
+        };
+        check("name", old_chain.name != new_chain.name);
+        check("rpc_url", old_chain.rpc_url != new_chain.rpc_url);
+        check("ws_url", old_chain.ws_url != new_chain.ws_url);
+        check("rpc", old_chain.rpc != new_chain.rpc);
+        check(
+            "start_block",
+            old_chain.start_block != new_chain.start_block,
+        );
+        check(
+            "block_batch_size",
+            old_chain.block_batch_size != new_chain.block_batch_size,
+        );
+        check(
+            "max_tx_retry",
+            old_chain.max_tx_retry != new_chain.max_tx_retry,
+        );
+        check(
+            "order_contract_address",
+            old_chain.order_contract_address != new_chain.order_contract_address,
+        );
+        check(
+            "multicall_address",
+            old_chain.multicall_address != new_chain.multicall_address,
+        );
+        check(
+            "filler_private_key",
+            old_chain.filler_private_key != new_chain.filler_private_key,
+        );
+        check("reconciler", old_chain.reconciler != new_chain.reconciler);
+    }
+    changes
+}
+
+/// The running services, restarted per chain as chains come and go on reload.
+struct Runtime {
+    config: AppConfig,
+    live_config: watch::Sender<Arc<LiveConfig>>,
+    chains: Vec<String>,
+    services: Vec<ServiceKind>,
+    join_set: JoinSet<()>,
+    chain_tasks: HashMap<u64, ChainTasks>,
+}
+
+/// Services of a running chain. The filler is asked to stop rather than aborted, so it does
+/// not leave a fill halfway.
+struct ChainTasks {
+    tasks: Vec<AbortHandle>,
+    shutdown: CancellationToken,
+}
+
+impl Runtime {
+    fn is_selected(&self, chain: &ChainConfig) -> bool {
+        self.chains.is_empty()
+            || self
+                .chains
+                .iter()
+                .any(|selected| *selected == chain.name || *selected == chain.chain_id.to_string())
+    }
+
+    fn runs(&self, service: ServiceKind) -> bool {
+        self.services.is_empty() || self.services.contains(&service)
+    }
+
+    fn start_chain(&mut self, chain: &ChainConfig) {
+        if !self.is_selected(chain) {
+            return;
+        }
+        info!(chain_name = chain.name, "Starting services");
+        let postgres_url = self.config.postgres_url.expose().to_string();
+        let live_config = self.live_config.subscribe();
+        let rpc_pool = live_config.borrow().rpc_pools[&chain.chain_id].clone();
+        let mut tasks = vec![];
+        let shutdown = CancellationToken::new();
+
+        if self.runs(ServiceKind::Listener) {
+            let listener = listener::Listener::new(
+                postgres_url.clone(),
+                chain.clone(),
+                rpc_pool.clone(),
+                live_config.clone(),
+            );
+            tasks.push(
+                self.join_set
+                    .spawn(async move { listener.await.unwrap().run().await }),
+            );
+        }
+
+        // Fillers of the selected chains still fill on any configured destination
+        if self.runs(ServiceKind::Filler) {
+            let filler = Filler::new(
+                postgres_url.clone(),
+                chain.clone(),
+                live_config.clone(),
+                shutdown.clone(),
+            );
+            self.join_set
+                .spawn(async move { filler.await.unwrap().run().await });
+        }
+
+        if self.runs(ServiceKind::Reconciler) {
+            let reconciler =
+                reconciler::Reconciler::new(postgres_url, chain.clone(), rpc_pool, live_config);
+            tasks.push(
+                self.join_set
+                    .spawn(async move { reconciler.await.unwrap().run().await }),
+            );
+        }
+        self.chain_tasks
+            .insert(chain.chain_id, ChainTasks { tasks, shutdown });
+    }
+
+    fn stop_chain(&mut self, chain: &ChainConfig) {
+        let Some(chain_tasks) = self.chain_tasks.remove(&chain.chain_id) else {
+            return;
+        };
+        info!(chain_name = chain.name, "Stopping services");
+        chain_tasks.shutdown.cancel();
+        for task in chain_tasks.tasks {
+            task.abort();
+        }
+        health().unregister_chain(&chain.name);
+    }
+
+    /// Applies the current configuration file, keeping the running one when it is invalid
+    /// or changes fields that need a restart.
+    async fn reload(&mut self) -> Result<()> {
+        let config = context::config()?;
+        config.validate()?;
+        let fixed_changes = fixed_changes(&self.config, &config);
+        if !fixed_changes.is_empty() {
+            return Err(eyre::eyre!(
+                "Changes need a restart: {}",
+                fixed_changes.join(", ")
+            ));
+        }
+        config.check_chain_ids().await?;
+
+        let previous = self.live_config.borrow().clone();
+        let live_config = LiveConfig::new(&config, Some(&previous)).await?;
+        let old_config = std::mem::replace(&mut self.config, config);
+        for chain in &old_config.chain {
+            if live_config.chain(chain.chain_id).is_none() {
+                self.stop_chain(chain);
+            }
+        }
+        self.live_config.send_replace(Arc::new(live_config));
+        for chain in &self.config.chain {
+            health().update_chain_config(&chain.name, &chain.health);
+        }
+        for chain in self.config.chain.clone() {
+            if !old_config
+                .chain
+                .iter()
+                .any(|old_chain| old_chain.chain_id == chain.chain_id)
+            {
+                self.start_chain(&chain);
+            }
+        }
+        info!("Configuration reloaded");
+        Ok(())
+    }
+}
+
+/// Signals a reload when `config.toml` changes or the process receives SIGHUP. The watcher
+/// stops when dropped.
+fn watch_config() -> Result<(impl Watcher, mpsc::Receiver<()>)> {
+    let (sender, receiver) = mpsc::channel(1);
+
+    let watcher_sender = sender.clone();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        let config_changed = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+            && event
+                .paths
+                .iter()
+                .any(|path| path.file_name().is_some_and(|name| name == CONFIG_FILE));
+        if config_changed {
+            // A reload is already pending otherwise
+            let _ = watcher_sender.try_send(());
+        }
+    })?;
+    // Editors often replace the file, so its directory is watched
+    watcher.watch(Path::new("."), RecursiveMode::NonRecursive)?;
+
+    let mut hangups = signal(SignalKind::hangup())?;
+    tokio::spawn(async move {
+        while hangups.recv().await.is_some() {
+            let _ = sender.try_send(());
+        }
+    });
+    Ok((watcher, receiver))
+}
+
+/// Runs the services of the selected chains, all of them when none is selected, and
+/// reloads the configuration while they run.
+pub async fn run(config: AppConfig, chains: Vec<String>, services: Vec<ServiceKind>) -> Result<()> {
+    info!("Bot is starting");
+    for chain in &chains {
+        find_chain(&config, chain)?;
+    }
+    alert::init(&config.alerts)?;
+    let live_config = LiveConfig::new(&config, None).await?;
+    let (live_config, _) = watch::channel(Arc::new(live_config));
+
+    let mut runtime = Runtime {
+        config,
+        live_config,
+        chains,
+        services,
+        join_set: JoinSet::new(),
+        chain_tasks: HashMap::new(),
+    };
+
+    let server_config = runtime.config.server.clone();
+    let postgres_url = runtime.config.postgres_url.expose().to_string();
+    let server_live_config = runtime.live_config.subscribe();
+    runtime.join_set.spawn(async move {
+        if let Err(e) = server::serve(&server_config, postgres_url, server_live_config).await {
+            error!(error = %e, "HTTP server stopped");
+        }
+    });
+
+    for chain in runtime.config.chain.clone() {
+        runtime.start_chain(&chain);
+    }
+
+    let (_watcher, mut reloads) = watch_config()?;
+    loop {
+        tokio::select! {
+            Some(res) = runtime.join_set.join_next() => {
+                if let Err(e) = res {
+                    if !e.is_cancelled() {
+                        return Err(e.into());
+                    }
+                }
+            }
+            Some(()) = reloads.recv() => {
+                tokio::time::sleep(RELOAD_DEBOUNCE).await;
+                while reloads.try_recv().is_ok() {}
+                info!("Reloading the configuration");
+                if let Err(e) = runtime.reload().await {
+                    error!(error = %e, "Configuration not reloaded, keeping the running one");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixed_changes;
+    use crate::context::{AppConfig, ChainConfig};
+
+    fn config(chains: Vec<ChainConfig>) -> AppConfig {
+        AppConfig {
+            chain: chains,
+            postgres_url: "postgres://localhost/bot".into(),
+            ..Default::default()
+        }
+    }
+
+    fn chain(chain_id: u64) -> ChainConfig {
+        ChainConfig {
+            name: format!("chain-{chain_id}"),
+            chain_id,
+            rpc_url: "http://localhost:8545".into(),
+            profitability_threshold: 0.1,
+            filler_poll_interval: 500,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_fixed_changes() {
+        let old = config(vec![chain(1), chain(2)]);
+
+        let mut reloadable = config(vec![chain(1), chain(3)]);
+        reloadable.chain[0].profitability_threshold = 0.2;
+        reloadable.chain[0].filler_poll_interval = 1_000;
+        reloadable.chain[0].health.max_lag_blocks = Some(10);
+        assert!(fixed_changes(&old, &reloadable).is_empty());
+
+        let mut fixed = config(vec![chain(1), chain(2)]);
+        fixed.postgres_url = "postgres://elsewhere/bot".into();
+        fixed.chain[1].rpc_url = "http://elsewhere:8545".into();
+        assert_eq!(
+            fixed_changes(&old, &fixed),
+            vec!["postgres_url", "chain chain-2: rpc_url"]
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::HashSet;
+
+use eyre::Result;
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{ConnectionTrait, Database, DbBackend, Statement, TransactionTrait};
+use tracing::info;
+
+/// Key of the advisory lock held while checking and applying migrations, so that replicas
+/// booting together migrate one after the other.
+const MIGRATION_LOCK_KEY: i64 = 0x696c_6179_6572;
+
+/// Checks the database schema against the migrations of this binary, applying the pending
+/// ones when `auto_migrate` is set.
+pub async fn prepare(postgres_url: &str, auto_migrate: bool) -> Result<()> {
+    let connection = Database::connect(postgres_url).await?;
+    let transaction = connection.begin().await?;
+    transaction
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT pg_advisory_xact_lock($1)",
+            [MIGRATION_LOCK_KEY.into()],
+        ))
+        .await?;
+
+    let applied = Migrator::get_migration_models(&transaction)
+        .await?
+        .into_iter()
+        .map(|migration| migration.version)
+        .collect::<Vec<_>>();
+    let known = Migrator::migrations()
+        .iter()
+        .map(|migration| migration.name().to_string())
+        .collect::<Vec<_>>();
+    let pending = pending_migrations(&applied, &known)?;
+
+    if !pending.is_empty() {
+        if !auto_migrate {
+            return Err(eyre::eyre!(
+                "{} pending database migrations, run `bot migrate up` or set `auto_migrate = true`",
+                pending.len()
+            ));
+        }
+        info!(migrations = ?pending, "Applying database migrations");
+        Migrator::up(&transaction, None).await?;
+    }
+    transaction.commit().await?;
+    Ok(())
+}
+
+/// Migrations of this binary missing from the database, failing when the database has
+/// migrations this binary doesn't know, i.e. it was migrated by a newer version.
+fn pending_migrations(applied: &[String], known: &[String]) -> Result<Vec<String>> {
+    let known_set = known.iter().collect::<HashSet<_>>();
+    let unknown = applied
+        .iter()
+        .filter(|version| !known_set.contains(version))
+        .cloned()
+        .collect::<Vec<_>>();
+    if !unknown.is_empty() {
+        return Err(eyre::eyre!(
+            "Database schema is ahead of this binary, unknown migrations: {}",
+            unknown.join(", ")
+        ));
+    }
+
+    let applied_set = applied.iter().collect::<HashSet<_>>();
+    Ok(known
+        .iter()
+        .filter(|version| !applied_set.contains(version))
+        .cloned()
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pending_migrations;
+
+    fn versions(versions: &[&str]) -> Vec<String> {
+        versions.iter().map(|version| version.to_string()).collect()
+    }
+
+    #[test]
+    fn test_pending_migrations() {
+        let known = versions(&["m1", "m2", "m3"]);
+        assert_eq!(
+            pending_migrations(&versions(&["m1"]), &known).unwrap(),
+            versions(&["m2", "m3"])
+        );
+        assert!(pending_migrations(&known, &known).unwrap().is_empty());
+        assert_eq!(pending_migrations(&[], &known).unwrap(), known);
+
+        let ahead = pending_migrations(&versions(&["m1", "m2", "m3", "m4"]), &known);
+        assert!(ahead.unwrap_err().to_string().contains("m4"));
+    }
+}
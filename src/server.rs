@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use chrono::Utc;
+use eyre::Result;
+use sea_orm::{ConnectOptions, Database, DatabaseConnection};
+use serde::Serialize;
+use tokio::net::TcpListener;
+use tracing::{error, info};
+
+use crate::{
+    api::{self, ApiState},
+    context::ServerConfig,
+    health::{health, ServiceHealth},
+    metrics::metrics,
+    runtime::LiveConfigReceiver,
+};
+
+const DEFAULT_LISTEN_ADDRESS: &str = "0.0.0.0:9090";
+
+#[derive(Clone)]
+struct ServerState {
+    connection: DatabaseConnection,
+}
+
+#[derive(Serialize)]
+struct ServiceReport {
+    #[serde(flatten)]
+    health: ServiceHealth,
+    lag_blocks: Option<u64>,
+    live: bool,
+    unready_reasons: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct HealthReport {
+    database: bool,
+    services: BTreeMap<String, ServiceReport>,
+}
+
+fn router(state: ServerState, api_state: ApiState, admin_token: Option<String>) -> Router {
+    Router::new()
+        .route("/metrics", get(get_metrics))
+        .route("/healthz", get(get_healthz))
+        .route("/readyz", get(get_readyz))
+        .with_state(state)
+        .nest("/api", api::router(api_state, admin_token))
+}
+
+async fn get_metrics() -> (StatusCode, String) {
+    match metrics().encode() {
+        Ok(encoded) => (StatusCode::OK, encoded),
+        Err(e) => {
+            error!(error = %e, "Unable to encode metrics");
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+        }
+    }
+}
+
+async fn health_report(state: &ServerState) -> HealthReport {
+    let now = Utc::now();
+    let services = health()
+        .services()
+        .into_iter()
+        .map(|(name, service_health)| {
+            let report = ServiceReport {
+                lag_blocks: service_health.lag_blocks(),
+                live: service_health.is_live(),
+                unready_reasons: service_health.unready_reasons(now),
+                health: service_health,
+            };
+            (name, report)
+        })
+        .collect();
+    HealthReport {
+        database: state.connection.ping().await.is_ok(),
+        services,
+    }
+}
+
+/// Fails when a service is stuck in its retry loop.
+async fn get_healthz(State(state): State<ServerState>) -> (StatusCode, Json<HealthReport>) {
+    let report = health_report(&state).await;
+    let live = report.services.values().all(|service| service.live);
+    let status = if live {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
+
+/// Fails when the database is unreachable or a service is not ready.
+async fn get_readyz(State(state): State<ServerState>) -> (StatusCode, Json<HealthReport>) {
+    let report = health_report(&state).await;
+    let ready = report.database
+        && report
+            .services
+            .values()
+            .all(|service| service.unready_reasons.is_empty());
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
+
+pub async fn serve(
+    config: &ServerConfig,
+    postgres_url: String,
+    live_config: LiveConfigReceiver,
+) -> Result<()> {
+    let listen_address = config
+        .listen_address
+        .as_deref()
+        .unwrap_or(DEFAULT_LISTEN_ADDRESS);
+    // Lazy, so the endpoints are served and report the database down rather than failing
+    let connection = Database::connect(
+        ConnectOptions::new(postgres_url.clone())
+            .connect_lazy(true)
+            .to_owned(),
+    )
+    .await?;
+    let api_state = ApiState::new(postgres_url, live_config).await?;
+    let listener = TcpListener::bind(listen_address).await?;
+    info!(listen_address, "HTTP server listening");
+
+    axum::serve(
+        listener,
+        router(
+            ServerState { connection },
+            api_state,
+            config
+                .admin_token
+                .as_ref()
+                .map(|admin_token| admin_token.expose().to_string()),
+        ),
+    )
+    .await?;
+    Ok(())
+}
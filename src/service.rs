@@ -1,21 +1,61 @@
 use eyre::Result;
-use tracing::{debug, error};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, info_span, Instrument};
+
+use crate::{
+    alert::{self, Alert, Severity},
+    context::ChainConfig,
+    health::health,
+    metrics::metrics,
+};
 
 pub trait Service {
     async fn run(&self) {
+        health().register(
+            &self.service_name(),
+            &self.chain_config().name,
+            &self.chain_config().health,
+        );
+        // Parent of the spans of the service, e.g. the orders it handles
+        let span = info_span!(
+            "service",
+            service = self.service_name(),
+            chain = self.chain_config().name
+        );
         loop {
-            match self._run().await {
-                Ok(()) => debug!(
-                    service = self.service_name(),
-                    "Service stopped unexpectedly...",
-                ),
-                Err(e) => error!(
-                    error = %e,
-                    service = self.service_name(),
-                    "Service error!"
-                ),
+            let result = self._run().instrument(span.clone()).await;
+            if self.shutdown().is_some_and(CancellationToken::is_cancelled) {
+                info!(service = self.service_name(), "Service stopped");
+                return;
+            }
+            match result {
+                Ok(()) => {
+                    debug!(
+                        service = self.service_name(),
+                        "Service stopped unexpectedly...",
+                    );
+                    health().record_error(&self.service_name(), "Service stopped".to_string());
+                }
+                Err(e) => {
+                    error!(
+                        error = %e,
+                        service = self.service_name(),
+                        "Service error!"
+                    );
+                    self.report_error(&e);
+                    alert::raise(Alert::new(
+                        Severity::Warning,
+                        "service_error",
+                        &self.chain_config().name,
+                        format!("{} restarting after an error: {e}", self.service_name()),
+                    ));
+                }
             }
 
+            metrics()
+                .service_restarts
+                .with_label_values(&[&self.service_name()])
+                .inc();
             // TODO Maybe we should make this configurable?
             tokio::time::sleep(std::time::Duration::from_secs(6)).await;
         }
@@ -24,4 +64,21 @@ pub trait Service {
     async fn _run(&self) -> Result<()>;
 
     fn service_name(&self) -> String;
+
+    fn chain_config(&self) -> &ChainConfig;
+
+    /// Cancelled when the service is asked to stop, `_run` returns at the next point where
+    /// stopping leaves nothing halfway.
+    fn shutdown(&self) -> Option<&CancellationToken> {
+        None
+    }
+
+    /// Marks a loop of the service as successful, for the health endpoints.
+    fn report_success(&self) {
+        health().record_success(&self.service_name());
+    }
+
+    fn report_error(&self, error: &eyre::Report) {
+        health().record_error(&self.service_name(), error.to_string());
+    }
 }
@@ -1,5 +1,13 @@
-use alloy::sol;
-use entity::{order, sea_orm_active_enums::OrderStatus};
+use alloy::{
+    primitives::{keccak256, Address, FixedBytes, U256},
+    rpc::types::trace::geth::CallFrame,
+    sol,
+    sol_types::{SolCall, SolType, SolValue},
+};
+use entity::{
+    order, order_token,
+    sea_orm_active_enums::{OrderStatus, TokenSide},
+};
 use eyre::Result;
 use sea_orm::ActiveValue;
 
@@ -20,20 +28,140 @@ sol!(
     "abi/Orderbook.abi.json"
 );
 
+sol!(
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    interface ERC20 {
+        function decimals() external view returns (uint8);
+        function symbol() external view returns (string);
+        function balanceOf(address account) external view returns (uint256);
+    }
+);
+
+sol!(
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    interface AggregatorV3 {
+        function decimals() external view returns (uint8);
+        function latestRoundData() external view returns (uint80 roundId, int256 answer, uint256 startedAt, uint256 updatedAt, uint80 answeredInRound);
+    }
+);
+
+/// Destination-side settler, only the entry points used by the filler are declared.
+/// The structs are ABI compatible with the ones generated for the `Orderbook`.
+pub mod settler {
+    use alloy::sol;
+
+    sol!(
+        #[allow(missing_docs)]
+        #[derive(Debug)]
+        struct _bytes64 {
+            bytes32 lower;
+            bytes32 upper;
+        }
+
+        #[allow(missing_docs)]
+        #[derive(Debug)]
+        library Validator {
+            struct Token {
+                _bytes64 tokenAddress;
+                uint256 tokenId;
+                uint256 amount;
+            }
+
+            struct Order {
+                _bytes64 user;
+                _bytes64 filler;
+                Token[] inputs;
+                Token[] outputs;
+                uint256 sourceChainSelector;
+                uint256 destinationChainSelector;
+                bool sponsored;
+                uint256 primaryFillerDeadline;
+                uint256 deadline;
+                _bytes64 callRecipient;
+                bytes callData;
+            }
+        }
+
+        #[allow(missing_docs)]
+        #[sol(rpc)]
+        interface Settler {
+            function fillOrder(Validator.Order memory order, bytes32 orderId, uint256 maxGas) external payable;
+        }
+    );
+}
+
+impl TryFrom<&Validator::Order> for settler::Validator::Order {
+    type Error = eyre::Error;
+
+    fn try_from(order: &Validator::Order) -> Result<Self> {
+        Ok(<settler::Validator::Order as SolType>::abi_decode(
+            &order.abi_encode(),
+            true,
+        )?)
+    }
+}
+
+pub fn bytes64_to_vec(bytes: &_bytes64) -> Vec<u8> {
+    let mut vec = bytes.lower.to_vec();
+    vec.extend(bytes.upper.iter());
+    vec
+}
+
+pub fn vec_to_bytes64(bytes: &[u8]) -> Result<_bytes64> {
+    if bytes.len() != 64 {
+        return Err(eyre::eyre!("Expected 64 bytes, got {}", bytes.len()));
+    }
+    Ok(_bytes64 {
+        lower: FixedBytes::from_slice(&bytes[..32]),
+        upper: FixedBytes::from_slice(&bytes[32..]),
+    })
+}
+
+/// Extracts the EVM address stored in the lower word of a `_bytes64`, if it holds one.
+pub fn bytes64_to_address(bytes: &[u8]) -> Option<Address> {
+    if bytes.len() != 64 || bytes[..12].iter().chain(&bytes[32..]).any(|b| *b != 0) {
+        return None;
+    }
+    Some(Address::from_slice(&bytes[12..32]))
+}
+
+/// Persisted `_bytes64` form of an EVM address.
+pub fn address_to_bytes64_vec(address: Address) -> Vec<u8> {
+    let mut vec = vec![0; 64];
+    vec[12..32].copy_from_slice(address.as_slice());
+    vec
+}
+
+pub fn u256_from_le_vec(bytes: &[u8]) -> Result<U256> {
+    U256::try_from_le_slice(bytes).ok_or(eyre::eyre!("Invalid uint256 encoding"))
+}
+
+/// Maps the `Validator.Status` returned by `orders(orderId)`, `None` for unknown orders.
+pub fn onchain_status_to_order_status(status: u8) -> Option<OrderStatus> {
+    match status {
+        1 => Some(OrderStatus::Created),
+        2 => Some(OrderStatus::Filled),
+        3 => Some(OrderStatus::Withdrawn),
+        _ => None,
+    }
+}
+
+/// Chain selectors are the EVM chain ids of the source and destination chains.
+pub fn chain_selector_to_chain_id(bytes: &[u8]) -> Result<u64> {
+    Ok(u256_from_le_vec(bytes)?.try_into()?)
+}
+
 pub fn map_solidity_order_to_model(
     chain_id: u64,
     order_id: Vec<u8>,
     order: &Validator::Order,
 ) -> Result<order::ActiveModel> {
     // TODO FIXME: Improve error handling
-    let mut user = order.user.lower.to_vec();
-    user.extend(order.user.upper.iter());
-
-    let mut filler = order.filler.lower.to_vec();
-    filler.extend(order.filler.upper.iter());
-
-    let mut call_recipient = order.callRecipient.lower.to_vec();
-    call_recipient.extend(order.callRecipient.upper.iter());
+    let user = bytes64_to_vec(&order.user);
+    let filler = bytes64_to_vec(&order.filler);
+    let call_recipient = bytes64_to_vec(&order.callRecipient);
 
     let deadline = chrono::DateTime::from_timestamp(order.deadline.to(), 0).unwrap();
     let primary_filler_deadline =
@@ -55,6 +183,136 @@ pub fn map_solidity_order_to_model(
         call_recipient: ActiveValue::Set(Some(call_recipient)),
         call_data: ActiveValue::Set(Some(call_data)),
         order_status: ActiveValue::Set(OrderStatus::Created),
+        rejection_reason: ActiveValue::NotSet,
+    })
+}
+
+pub fn decode_receive_message(input: &[u8]) -> Option<Orderbook::receiveMessageCall> {
+    if !input.starts_with(&Orderbook::receiveMessageCall::SELECTOR) {
+        return None;
+    }
+    Orderbook::receiveMessageCall::abi_decode(input, true).ok()
+}
+
+/// Whether a settlement message is about the order: `hashedData` has to commit to the
+/// message data, which carries the order id.
+pub fn receive_message_settles(call: &Orderbook::receiveMessageCall, order_id: &[u8]) -> bool {
+    keccak256(&call.messageData) == call.message.hashedData
+        && call.messageData.chunks(32).any(|word| word == order_id)
+}
+
+/// Walks a call trace looking for the `receiveMessage` call made to the orderbook that
+/// settles the order.
+pub fn find_receive_message(
+    frame: &CallFrame,
+    orderbook: Address,
+    order_id: &[u8],
+) -> Option<Orderbook::receiveMessageCall> {
+    if frame.to == Some(orderbook) {
+        if let Some(call) = decode_receive_message(&frame.input)
+            .filter(|call| receive_message_settles(call, order_id))
+        {
+            return Some(call);
+        }
+    }
+    frame
+        .calls
+        .iter()
+        .find_map(|frame| find_receive_message(frame, orderbook, order_id))
+}
+
+/// JSON form of an order as emitted on chain, stored as the payload of its events.
+pub fn order_to_json(order: &Validator::Order) -> serde_json::Value {
+    let tokens_to_json = |tokens: &[Validator::Token]| {
+        tokens
+            .iter()
+            .map(|token| {
+                serde_json::json!({
+                    "tokenAddress": hex::encode(bytes64_to_vec(&token.tokenAddress)),
+                    "tokenId": token.tokenId.to_string(),
+                    "amount": token.amount.to_string(),
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+
+    serde_json::json!({
+        "user": hex::encode(bytes64_to_vec(&order.user)),
+        "filler": hex::encode(bytes64_to_vec(&order.filler)),
+        "inputs": tokens_to_json(&order.inputs),
+        "outputs": tokens_to_json(&order.outputs),
+        "sourceChainSelector": order.sourceChainSelector.to_string(),
+        "destinationChainSelector": order.destinationChainSelector.to_string(),
+        "sponsored": order.sponsored,
+        "primaryFillerDeadline": order.primaryFillerDeadline.to_string(),
+        "deadline": order.deadline.to_string(),
+        "callRecipient": hex::encode(bytes64_to_vec(&order.callRecipient)),
+        "callData": hex::encode(&order.callData),
+    })
+}
+
+pub fn map_solidity_tokens_to_models(
+    order_id: Vec<u8>,
+    order: &Validator::Order,
+) -> Vec<order_token::ActiveModel> {
+    let inputs = order
+        .inputs
+        .iter()
+        .enumerate()
+        .map(|(position, token)| (TokenSide::Input, position, token));
+    let outputs = order
+        .outputs
+        .iter()
+        .enumerate()
+        .map(|(position, token)| (TokenSide::Output, position, token));
+
+    inputs
+        .chain(outputs)
+        .map(|(side, position, token)| order_token::ActiveModel {
+            id: ActiveValue::NotSet,
+            order_id: ActiveValue::set(order_id.clone()),
+            side: ActiveValue::set(side),
+            position: ActiveValue::set(position as i32),
+            token_address: ActiveValue::set(bytes64_to_vec(&token.tokenAddress)),
+            token_id: ActiveValue::set(token.tokenId.as_le_bytes().to_vec()),
+            amount: ActiveValue::set(token.amount.as_le_bytes().to_vec()),
+        })
+        .collect()
+}
+
+/// Rebuilds the on-chain order struct from its persisted representation.
+pub fn map_model_to_solidity_order(
+    order: &order::Model,
+    tokens: &[order_token::Model],
+) -> Result<Validator::Order> {
+    let map_tokens = |side: TokenSide| -> Result<Vec<Validator::Token>> {
+        let mut tokens: Vec<&order_token::Model> =
+            tokens.iter().filter(|token| token.side == side).collect();
+        tokens.sort_by_key(|token| token.position);
+        tokens
+            .into_iter()
+            .map(|token| {
+                Ok(Validator::Token {
+                    tokenAddress: vec_to_bytes64(&token.token_address)?,
+                    tokenId: u256_from_le_vec(&token.token_id)?,
+                    amount: u256_from_le_vec(&token.amount)?,
+                })
+            })
+            .collect()
+    };
+
+    Ok(Validator::Order {
+        user: vec_to_bytes64(&order.user)?,
+        filler: vec_to_bytes64(&order.filler)?,
+        inputs: map_tokens(TokenSide::Input)?,
+        outputs: map_tokens(TokenSide::Output)?,
+        sourceChainSelector: u256_from_le_vec(&order.source_chain_selector)?,
+        destinationChainSelector: u256_from_le_vec(&order.destination_chain_selector)?,
+        sponsored: order.sponsored,
+        primaryFillerDeadline: U256::from(order.primary_filler_deadline.and_utc().timestamp()),
+        deadline: U256::from(order.deadline.and_utc().timestamp()),
+        callRecipient: vec_to_bytes64(order.call_recipient.as_deref().unwrap_or(&[0; 64]))?,
+        callData: order.call_data.clone().unwrap_or_default().into(),
     })
 }
 
@@ -98,64 +356,102 @@ impl std::fmt::Debug for Orderbook::OrderFilled {
     }
 }
 
+/// `OrderCreated` of an ERC20 to ERC20 order, as emitted by the orderbook on a local node.
+#[cfg(test)]
+pub(crate) fn order_created_fixture() -> Orderbook::OrderCreated {
+    use std::str::FromStr;
+
+    use alloy::{
+        primitives::{Bytes, Log},
+        sol_types::SolEvent,
+    };
+
+    let topics: Vec<FixedBytes<32>> = vec![
+        FixedBytes::from_str("0x1f3e9ee381e3de37fa4a5d5d5e8e320b51fd6547b591c80a169dbcf6160878e3")
+            .unwrap(),
+        FixedBytes::from_str("0x777a108f0d7d6ef99218eb59bc1900ed56d401db4fc9bbff76d85c68c5cb0168")
+            .unwrap(),
+    ];
+    let data = Bytes::from_str(
+        "0x000000000000000000000000a0ee7a142d267c1f36714e4a8f75612f20a7972\
+        0000000000000000000000000000000000000000000000000000000000000006000\
+        0000000000000000000000000000000000000000000000000000000000000000000\
+        0000000000000000000a0ee7a142d267c1f36714e4a8f75612f20a7972000000000\
+        0000000000000000000000000000000000000000000000000000000000000000000\
+        000000000000023618e81e3f5cdf7f54c3d65f7fbc0abf5b21e8f00000000000000\
+        0000000000000000000000000000000000000000000000000000000000000000000\
+        000000000000000000000000000000000000000000001c000000000000000000000\
+        0000000000000000000000000000000000000000026000000000000000000000000\
+        00000000000000000000000000000000000007a6900000000000000000000000000\
+        00000000000000000000000000000000007a6900000000000000000000000000000\
+        0000000000000000000000000000000000000000000000000000000000000000000\
+        0000000000000000000000006772a62200000000000000000000000000000000000\
+        00000000000000000000068a3ac1200000000000000000000000000000000000000\
+        0000000000000000000000000000000000000000000000000000000000000000000\
+        0000000000000000000000000000000000000000000000000000000000000000000\
+        0000000000000000030000000000000000000000000000000000000000000000000\
+        00000000000000001000000000000000000000000700b6a60ce7eaaea56f065753d\
+        8dcb9653dbad3500000000000000000000000000000000000000000000000000000\
+        00000000000ffffffffffffffffffffffffffffffffffffffffffffffffffffffff\
+        ffffffff0000000000000000000000000000000000000000000000000de0b6b3a76\
+        4000000000000000000000000000000000000000000000000000000000000000000\
+        01000000000000000000000000a15bb66138824a1c7167f5e85b957d04dd34e4680\
+        000000000000000000000000000000000000000000000000000000000000000ffff\
+        ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff0000000\
+        000000000000000000000000000000000000000000de0b6b3a76400000000000000\
+        000000000000000000000000000000000000000000000000000000",
+    )
+    .unwrap();
+
+    let address = Address::from_str("0x8ce361602b935680e8dec218b820ff5056beb7af").unwrap();
+    let log = Log::new(address, topics, data).unwrap();
+    Orderbook::OrderCreated::decode_log(&log, false)
+        .unwrap()
+        .data
+}
+
 #[cfg(test)]
 mod tests {
     use alloy::{
-        primitives::{Address, Bytes, FixedBytes, Log},
+        primitives::{Address, Bytes, U256},
+        rpc::types::trace::geth::CallFrame,
         signers::local::PrivateKeySigner,
-        sol_types::SolEvent,
+        sol_types::SolValue,
     };
-    use entity::order;
+    use entity::{order, order_token};
+    use sea_orm::ActiveValue;
     use std::str::FromStr;
 
-    use super::{map_solidity_order_to_model, Orderbook};
+    use super::{
+        address_to_bytes64_vec, bytes64_to_address, bytes64_to_vec, decode_receive_message,
+        find_receive_message, map_model_to_solidity_order, map_solidity_order_to_model,
+        map_solidity_tokens_to_models, order_created_fixture, receive_message_settles,
+        vec_to_bytes64, Validator,
+    };
+
+    /// `receiveMessage` of a settlement relayed by a dispatcher, its message data is
+    /// `abi.encode(orderId, filler, 1)`.
+    const RECEIVE_MESSAGE: &str =
+        "0x783f917f0000000000000000000000009fe46736679d2d9a65f0992f2272de9f3c7fa6\
+    e000000000000000000000000000000000000000000000000000000000000004d2000000\
+    000000000000000000000000000000000000000000000000000000000c00000000000000\
+    0000000000000000000000000000000000000000000000a4b10000000000000000000000\
+    00e7f1725e7734ce288f8367e1bb143e90bb3f0512000000000000000000000000000000\
+    000000000000000000000000000000000000000000000000000000000000000000000000\
+    0000000000000000000000000a0000000000000000000000008ce361602b935680e8dec2\
+    18b820ff5056beb7af000000000000000000000000000000000000000000000000000000\
+    0000000000531e2d95d38ab753d114c903e3ab836a624ec15a8965d7f89ba8ea2c68808d\
+    680000000000000000000000000000000000000000000000000000000000000180000000\
+    000000000000000000000000000000000000000000000000000000020000000000000000\
+    000000000000000000000000000000000000000000000000607777777777777777777777\
+    777777777777777777777777777777777777777777000000000000000000000000232323\
+    232323232323232323232323232323232300000000000000000000000000000000000000\
+    000000000000000000000000010000000000000000000000000000000000000000000000\
+    000000000000000000";
 
     #[test]
     fn test_ordercreated_decode() {
-        let topics: Vec<FixedBytes<32>> = vec![
-            FixedBytes::from_str(
-                "0x1f3e9ee381e3de37fa4a5d5d5e8e320b51fd6547b591c80a169dbcf6160878e3",
-            )
-            .unwrap(),
-            FixedBytes::from_str(
-                "0x777a108f0d7d6ef99218eb59bc1900ed56d401db4fc9bbff76d85c68c5cb0168",
-            )
-            .unwrap(),
-        ];
-        let data = Bytes::from_str(
-            "0x000000000000000000000000a0ee7a142d267c1f36714e4a8f75612f20a7972\
-            0000000000000000000000000000000000000000000000000000000000000006000\
-            0000000000000000000000000000000000000000000000000000000000000000000\
-            0000000000000000000a0ee7a142d267c1f36714e4a8f75612f20a7972000000000\
-            0000000000000000000000000000000000000000000000000000000000000000000\
-            000000000000023618e81e3f5cdf7f54c3d65f7fbc0abf5b21e8f00000000000000\
-            0000000000000000000000000000000000000000000000000000000000000000000\
-            000000000000000000000000000000000000000000001c000000000000000000000\
-            0000000000000000000000000000000000000000026000000000000000000000000\
-            00000000000000000000000000000000000007a6900000000000000000000000000\
-            00000000000000000000000000000000007a6900000000000000000000000000000\
-            0000000000000000000000000000000000000000000000000000000000000000000\
-            0000000000000000000000006772a62200000000000000000000000000000000000\
-            00000000000000000000068a3ac1200000000000000000000000000000000000000\
-            0000000000000000000000000000000000000000000000000000000000000000000\
-            0000000000000000000000000000000000000000000000000000000000000000000\
-            0000000000000000030000000000000000000000000000000000000000000000000\
-            00000000000000001000000000000000000000000700b6a60ce7eaaea56f065753d\
-            8dcb9653dbad3500000000000000000000000000000000000000000000000000000\
-            00000000000ffffffffffffffffffffffffffffffffffffffffffffffffffffffff\
-            ffffffff0000000000000000000000000000000000000000000000000de0b6b3a76\
-            4000000000000000000000000000000000000000000000000000000000000000000\
-            01000000000000000000000000a15bb66138824a1c7167f5e85b957d04dd34e4680\
-            000000000000000000000000000000000000000000000000000000000000000ffff\
-            ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff0000000\
-            000000000000000000000000000000000000000000de0b6b3a76400000000000000\
-            000000000000000000000000000000000000000000000000000000",
-        )
-        .unwrap();
-
-        let address = Address::from_str("0x8ce361602b935680e8dec218b820ff5056beb7af").unwrap();
-        let log = Log::new(address, topics, data).unwrap();
-        let order_created = Orderbook::OrderCreated::decode_log(&log, false).unwrap();
+        let order_created = order_created_fixture();
         let actual: order::ActiveModel = map_solidity_order_to_model(
             1,
             "0x777a108f0d7d6ef99218eb59bc1900ed56d401db4fc9bbff76d85c68c5cb0168"
@@ -218,6 +514,135 @@ mod tests {
         assert_eq!(actual.call_data.unwrap().unwrap(), call_data);
     }
 
+    #[test]
+    fn test_decode_receive_message() {
+        let input = Bytes::from_str(RECEIVE_MESSAGE).unwrap();
+        let call = decode_receive_message(&input).unwrap();
+        assert_eq!(
+            call.dispatcher,
+            Address::from_str("0x9fE46736679d2D9a65F0992F2272dE9f3c7fa6e0").unwrap()
+        );
+        assert_eq!(call.message.blockNumber, U256::from(1234));
+        assert_eq!(call.message.blockConfirmations, 12);
+        assert_eq!(call.message.sourceChainSelector, U256::from(42161));
+        assert_eq!(
+            bytes64_to_address(&[call.message.sender.lower, call.message.sender.upper].concat()),
+            Some(Address::from_str("0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512").unwrap())
+        );
+
+        assert!(receive_message_settles(&call, &[0x77; 32]));
+        assert!(!receive_message_settles(&call, &[0x78; 32]));
+        let mut tampered = call.clone();
+        tampered.messageData =
+            Bytes::from([[0x78; 32].as_slice(), &call.messageData[32..]].concat());
+        assert!(!receive_message_settles(&tampered, &[0x78; 32]));
+
+        assert!(decode_receive_message(&input[4..]).is_none());
+        assert!(decode_receive_message(&input[..100]).is_none());
+    }
+
+    #[test]
+    fn test_find_receive_message() {
+        let orderbook = Address::from_str("0x8ce361602b935680e8dec218b820ff5056beb7af").unwrap();
+        // callTracer frame of a dispatcher delivering the message
+        let frame: CallFrame = serde_json::from_value(serde_json::json!({
+            "type": "CALL",
+            "from": "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266",
+            "to": "0x9fe46736679d2d9a65f0992f2272de9f3c7fa6e0",
+            "gas": "0x7a120",
+            "gasUsed": "0x1d4c0",
+            "input": "0x",
+            "calls": [
+                {
+                    "type": "STATICCALL",
+                    "from": "0x9fe46736679d2d9a65f0992f2272de9f3c7fa6e0",
+                    "to": "0x5fbdb2315678afecb367f032d93f642f64180aa3",
+                    "gas": "0x61a80",
+                    "gasUsed": "0x2710",
+                    "input": "0x12345678"
+                },
+                {
+                    "type": "CALL",
+                    "from": "0x9fe46736679d2d9a65f0992f2272de9f3c7fa6e0",
+                    "to": "0x8ce361602b935680e8dec218b820ff5056beb7af",
+                    "gas": "0x61a80",
+                    "gasUsed": "0x186a0",
+                    "input": RECEIVE_MESSAGE
+                }
+            ]
+        }))
+        .unwrap();
+
+        let call = find_receive_message(&frame, orderbook, &[0x77; 32]).unwrap();
+        assert_eq!(call.message.destinationChainSelector, U256::from(10));
+        assert!(find_receive_message(&frame, orderbook, &[0x78; 32]).is_none());
+        assert!(find_receive_message(&frame, Address::repeat_byte(1), &[0x77; 32]).is_none());
+    }
+
+    fn order() -> Validator::Order {
+        let bytes64 = |address: &str| {
+            vec_to_bytes64(&address_to_bytes64_vec(Address::from_str(address).unwrap())).unwrap()
+        };
+        let token = |address: &str| Validator::Token {
+            tokenAddress: bytes64(address),
+            tokenId: U256::MAX,
+            amount: U256::from(10).pow(U256::from(18)),
+        };
+        Validator::Order {
+            user: bytes64("0xa0Ee7A142d267C1f36714E4a8F75612F20a79720"),
+            filler: bytes64("0x23618e81E3f5cdF7f54C3d65f7FBc0aBf5B21E8f"),
+            inputs: vec![token("0x700b6A60ce7EaaEA56F065753d8dcB9653dbAD35")],
+            outputs: vec![token("0xA15BB66138824a1c7167f5E85b957d04Dd34E468")],
+            sourceChainSelector: U256::from(31337),
+            destinationChainSelector: U256::from(31337),
+            sponsored: false,
+            primaryFillerDeadline: U256::from(1735566882),
+            deadline: U256::from(1755556882),
+            callRecipient: vec_to_bytes64(&[0; 64]).unwrap(),
+            callData: Bytes::new(),
+        }
+    }
+
+    #[test]
+    fn test_order_model_roundtrip() {
+        let solidity_order = order();
+        let order_id = vec![7; 32];
+
+        let mut order = map_solidity_order_to_model(1, order_id.clone(), &solidity_order).unwrap();
+        order.id = ActiveValue::set(1);
+        order.rejection_reason = ActiveValue::set(None);
+        let order: order::Model = order.try_into().unwrap();
+        let tokens: Vec<order_token::Model> =
+            map_solidity_tokens_to_models(order_id, &solidity_order)
+                .into_iter()
+                .enumerate()
+                .map(|(id, mut token)| {
+                    token.id = ActiveValue::set(id as i32);
+                    token.try_into().unwrap()
+                })
+                .collect();
+
+        assert_eq!(tokens.len(), 2);
+        let actual = map_model_to_solidity_order(&order, &tokens).unwrap();
+        assert_eq!(actual.abi_encode(), solidity_order.abi_encode());
+    }
+
+    #[test]
+    fn test_bytes64_to_address() {
+        let mut bytes = bytes64_to_vec(&order().user);
+
+        assert_eq!(
+            bytes64_to_address(&bytes),
+            Some(Address::from_str("0xa0Ee7A142d267C1f36714E4a8F75612F20a79720").unwrap())
+        );
+        assert_eq!(
+            address_to_bytes64_vec(bytes64_to_address(&bytes).unwrap()),
+            bytes
+        );
+        bytes[63] = 1;
+        assert_eq!(bytes64_to_address(&bytes), None);
+    }
+
     #[test]
     fn test_priv_key_to_address_conversion() {
         // This is a placeholder for the actual test implementation.
@@ -0,0 +1,108 @@
+use eyre::Result;
+use opentelemetry::{trace::TracerProvider, Context};
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{trace::SdkTracerProvider, Resource};
+use tracing::{warn, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+
+use crate::context::{LogConfig, LogFormat};
+
+const DEFAULT_SERVICE_NAME: &str = "ilayer-bot";
+
+/// Flushes the exported spans when dropped.
+pub struct Telemetry {
+    tracer_provider: Option<SdkTracerProvider>,
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if let Some(tracer_provider) = &self.tracer_provider {
+            if let Err(e) = tracer_provider.shutdown() {
+                warn!(error = %e, "Unable to export the last spans");
+            }
+        }
+    }
+}
+
+/// Installs the log output and, when an OTLP endpoint is configured, the span exporter.
+pub fn init(config: &LogConfig) -> Result<Telemetry> {
+    let env_filter = EnvFilter::try_from_default_env().or_else(|_| EnvFilter::try_new("info"))?;
+    let fmt_layer = match config.format {
+        LogFormat::Text => tracing_subscriber::fmt::layer().boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer().json().boxed(),
+    };
+
+    let tracer_provider = match &config.otlp_endpoint {
+        Some(otlp_endpoint) => {
+            let exporter = SpanExporter::builder()
+                .with_http()
+                .with_endpoint(otlp_endpoint)
+                .build()?;
+            let service_name = config
+                .service_name
+                .clone()
+                .unwrap_or(DEFAULT_SERVICE_NAME.to_string());
+            Some(
+                SdkTracerProvider::builder()
+                    .with_batch_exporter(exporter)
+                    .with_resource(Resource::builder().with_service_name(service_name).build())
+                    .build(),
+            )
+        }
+        None => None,
+    };
+    let otel_layer = tracer_provider.as_ref().map(|tracer_provider| {
+        tracing_opentelemetry::layer().with_tracer(tracer_provider.tracer(DEFAULT_SERVICE_NAME))
+    });
+
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(fmt_layer)
+        .with(otel_layer)
+        .try_init()?;
+    Ok(Telemetry { tracer_provider })
+}
+
+/// Makes the span of an order the root of its own trace rather than a child of the long
+/// running service span, and records the `order_id` attribute the traces of an order are
+/// searched by. The span must declare an `order_id` field.
+pub fn order_root_span(span: &Span, order_id: &[u8]) {
+    span.record("order_id", hex::encode(order_id));
+    // Fails without an exporter, the span is only logged then
+    let _ = span.set_parent(Context::new());
+}
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry::trace::{TraceContextExt, TracerProvider};
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use tracing::{field, info_span};
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::order_root_span;
+
+    #[test]
+    fn test_order_spans_are_roots() {
+        let tracer_provider = SdkTracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(tracer_provider.tracer("test")));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let trace_id = |span: &tracing::Span| span.context().span().span_context().trace_id();
+            let service_span = info_span!("service");
+            let _entered = service_span.enter();
+
+            let child_span = info_span!("child");
+            assert_eq!(trace_id(&child_span), trace_id(&service_span));
+
+            let order_span = info_span!("fill_order", order_id = field::Empty);
+            order_root_span(&order_span, &[0x11; 32]);
+            assert_ne!(trace_id(&order_span), trace_id(&service_span));
+            let other_order_span = info_span!("fill_order", order_id = field::Empty);
+            order_root_span(&other_order_span, &[0x11; 32]);
+            assert_ne!(trace_id(&other_order_span), trace_id(&order_span));
+        });
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use alloy::primitives::{Address, U256};
+use eyre::Result;
+use tracing::{info, warn};
+
+use crate::{
+    api::format::{format_amount, format_bytes64},
+    context::{ChainConfig, TokenConfig},
+    multicall::{aggregate, Multicall},
+    rpc::RpcPool,
+    solidity::{bytes64_to_address, bytes64_to_vec, Validator, ERC20},
+};
+
+/// Configured tokens of every chain, looked up by their `_bytes64` address.
+#[derive(Debug, Default)]
+pub struct TokenRegistry {
+    tokens: HashMap<(u64, Address), TokenConfig>,
+}
+
+impl TokenRegistry {
+    pub fn new(chains: &[ChainConfig]) -> Self {
+        let tokens = chains
+            .iter()
+            .flat_map(|chain| {
+                chain.tokens.iter().filter_map(|token| {
+                    let address = token.address.parse::<Address>().ok()?;
+                    Some(((chain.chain_id, address), token.clone()))
+                })
+            })
+            .collect();
+        Self { tokens }
+    }
+
+    pub fn resolve(&self, chain_id: u64, token_address: &[u8]) -> Option<&TokenConfig> {
+        let address = bytes64_to_address(token_address)?;
+        self.tokens.get(&(chain_id, address))
+    }
+
+    /// Amount in whole units followed by the token symbol when the token is known, e.g.
+    /// `1.5 USDC`, the raw amount and token address otherwise.
+    pub fn format_amount(&self, chain_id: u64, token_address: &[u8], amount: U256) -> String {
+        let token = self.resolve(chain_id, token_address);
+        match token.and_then(|token| Some((token, token.decimals?))) {
+            Some((token, decimals)) => match format_amount(amount, decimals) {
+                Ok(formatted) => format!("{formatted} {}", token.symbol),
+                Err(_) => format!("{amount} {}", token.symbol),
+            },
+            None => format!("{amount} {}", format_bytes64(token_address)),
+        }
+    }
+
+    /// Token legs of an order for logging, e.g. `1.5 USDC, 0.2 WETH`.
+    pub fn format_tokens(&self, chain_id: u64, tokens: &[Validator::Token]) -> String {
+        tokens
+            .iter()
+            .map(|token| {
+                self.format_amount(chain_id, &bytes64_to_vec(&token.tokenAddress), token.amount)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Reads the decimals and symbol missing from the chain's ERC20 tokens from their
+/// contracts. Tokens that cannot be read keep their configuration.
+pub async fn fetch_token_metadata(chain: &mut ChainConfig, rpc_pool: &RpcPool) -> Result<()> {
+    let missing = chain
+        .tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, token)| {
+            token.token_type.eq_ignore_ascii_case("ERC20")
+                && (token.decimals.is_none() || token.symbol.is_empty())
+        })
+        .filter_map(|(i, token)| Some((i, token.address.parse::<Address>().ok()?)))
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    let provider = rpc_pool.provider().await?;
+    let multicall_address = chain
+        .multicall_address
+        .as_deref()
+        .map(str::parse)
+        .transpose()?;
+    let multicall = || {
+        let multicall = Multicall::new(&provider);
+        match multicall_address {
+            Some(address) => multicall.with_address(address),
+            None => multicall,
+        }
+    };
+    let decimals = aggregate(
+        multicall(),
+        missing
+            .iter()
+            .map(|(_, address)| (*address, ERC20::decimalsCall {})),
+    )
+    .await?;
+    let symbols = aggregate(
+        multicall(),
+        missing
+            .iter()
+            .map(|(_, address)| (*address, ERC20::symbolCall {})),
+    )
+    .await?;
+
+    for (((i, address), decimals), symbol) in missing.into_iter().zip(decimals).zip(symbols) {
+        let token = &mut chain.tokens[i];
+        if token.decimals.is_none() {
+            match decimals {
+                Ok(decimals) => token.decimals = Some(decimals._0),
+                Err(e) => warn!(
+                    chain_name = chain.name,
+                    %address,
+                    error = ?e,
+                    "Unable to read the token decimals"
+                ),
+            }
+        }
+        if token.symbol.is_empty() {
+            match symbol {
+                Ok(symbol) => token.symbol = symbol._0,
+                Err(e) => warn!(
+                    chain_name = chain.name,
+                    %address,
+                    error = ?e,
+                    "Unable to read the token symbol"
+                ),
+            }
+        }
+        info!(
+            chain_name = chain.name,
+            %address,
+            symbol = token.symbol,
+            decimals = token.decimals,
+            "Token metadata read on-chain"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{address, U256};
+
+    use super::TokenRegistry;
+    use crate::{
+        context::{ChainConfig, TokenConfig},
+        solidity::address_to_bytes64_vec,
+    };
+
+    #[test]
+    fn test_token_registry() {
+        let usdc = address!("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48");
+        let chain = ChainConfig {
+            chain_id: 1,
+            tokens: vec![TokenConfig {
+                symbol: "USDC".to_string(),
+                token_type: "ERC20".to_string(),
+                address: usdc.to_string(),
+                decimals: Some(6),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let registry = TokenRegistry::new(&[chain]);
+        let token_address = address_to_bytes64_vec(usdc);
+
+        assert_eq!(registry.resolve(1, &token_address).unwrap().symbol, "USDC");
+        assert!(registry.resolve(2, &token_address).is_none());
+        assert!(registry.resolve(1, &[0; 64]).is_none());
+        assert_eq!(
+            registry.format_amount(1, &token_address, U256::from(1_500_000)),
+            "1.5 USDC"
+        );
+        assert_eq!(
+            registry.format_amount(2, &token_address, U256::from(1_500_000)),
+            format!("1500000 {usdc}")
+        );
+    }
+}